@@ -0,0 +1,5 @@
+-- Position keys reached during the game (one per line), used for threefold repetition
+ALTER TABLE games ADD COLUMN position_history TEXT NOT NULL DEFAULT '';
+
+-- Which rule ended a drawn game (stalemate, insufficient_material, fifty_move_rule, threefold_repetition)
+ALTER TABLE games ADD COLUMN draw_reason TEXT;
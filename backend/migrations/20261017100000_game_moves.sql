@@ -0,0 +1,16 @@
+-- Full move history of every game (one row per ply)
+CREATE TABLE game_moves (
+    id TEXT PRIMARY KEY NOT NULL,
+    game_id TEXT NOT NULL,
+    ply INTEGER NOT NULL,
+    side TEXT NOT NULL,
+    uci TEXT NOT NULL,
+    san TEXT NOT NULL,
+    fen_after TEXT NOT NULL,
+    think_time_ms INTEGER,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE(game_id, ply),
+    FOREIGN KEY (game_id) REFERENCES games(id)
+);
+
+CREATE INDEX idx_game_moves_game_id ON game_moves(game_id);
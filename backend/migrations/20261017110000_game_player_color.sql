@@ -0,0 +1,2 @@
+-- Side played by the human ("white" or "black"); Stockfish plays the other one
+ALTER TABLE games ADD COLUMN player_color TEXT NOT NULL DEFAULT 'white';
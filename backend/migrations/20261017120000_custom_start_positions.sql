@@ -0,0 +1,19 @@
+-- Starting position of games that did not start from the standard position
+ALTER TABLE games ADD COLUMN start_fen TEXT;
+
+-- Statistics for games outside the standard mode (e.g. custom positions), kept apart
+-- from user_level_stats so drills don't skew the regular stats, records and ELO
+CREATE TABLE user_mode_stats (
+    id TEXT PRIMARY KEY NOT NULL,
+    user_id TEXT NOT NULL,
+    mode TEXT NOT NULL,
+    difficulty INTEGER NOT NULL,
+    games_played INTEGER NOT NULL DEFAULT 0,
+    games_won INTEGER NOT NULL DEFAULT 0,
+    total_time_seconds INTEGER NOT NULL DEFAULT 0,
+    total_moves INTEGER NOT NULL DEFAULT 0,
+    UNIQUE(user_id, mode, difficulty),
+    FOREIGN KEY (user_id) REFERENCES users(id)
+);
+
+CREATE INDEX idx_user_mode_stats_user_id ON user_mode_stats(user_id);
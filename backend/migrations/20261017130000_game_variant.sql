@@ -0,0 +1,2 @@
+-- Rules variant of the game ("standard" or "chess960")
+ALTER TABLE games ADD COLUMN variant TEXT NOT NULL DEFAULT 'standard';
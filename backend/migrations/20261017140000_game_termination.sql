@@ -0,0 +1,6 @@
+-- How a finished or aborted game ended (checkmate, resignation, agreement, aborted...)
+ALTER TABLE games ADD COLUMN termination TEXT;
+
+-- Backfill games finished before this column existed
+UPDATE games SET termination = draw_reason WHERE status = 'finished' AND draw_reason IS NOT NULL;
+UPDATE games SET termination = 'checkmate' WHERE status = 'finished' AND draw_reason IS NULL AND result IN ('white', 'black');
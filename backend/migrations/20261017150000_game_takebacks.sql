@@ -0,0 +1,3 @@
+-- Number of takebacks used in a game, and whether the player allowed them when creating it
+ALTER TABLE games ADD COLUMN takebacks INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE games ADD COLUMN takebacks_allowed BOOLEAN NOT NULL DEFAULT 1;
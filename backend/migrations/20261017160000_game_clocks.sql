@@ -0,0 +1,12 @@
+-- Time control: initial time and increment per side (NULL base = untimed game);
+-- correspondence games reset the clock to the base time after every move
+ALTER TABLE games ADD COLUMN clock_base_ms INTEGER;
+ALTER TABLE games ADD COLUMN clock_increment_ms INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE games ADD COLUMN clock_per_move BOOLEAN NOT NULL DEFAULT 0;
+
+-- Remaining time of each side when its clock last stopped
+ALTER TABLE games ADD COLUMN white_clock_ms INTEGER;
+ALTER TABLE games ADD COLUMN black_clock_ms INTEGER;
+
+-- When the side to move started thinking
+ALTER TABLE games ADD COLUMN turn_started_at DATETIME;
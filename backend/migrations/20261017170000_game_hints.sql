@@ -0,0 +1,2 @@
+-- Number of engine hints the player asked for in a game
+ALTER TABLE games ADD COLUMN hints_used INTEGER NOT NULL DEFAULT 0;
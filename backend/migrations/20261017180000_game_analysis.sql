@@ -0,0 +1,24 @@
+-- Post-game engine analysis: queue status on the game, one row per analysed ply
+ALTER TABLE games ADD COLUMN analysis_status TEXT;
+
+CREATE TABLE game_analysis (
+    id TEXT PRIMARY KEY NOT NULL,
+    game_id TEXT NOT NULL,
+    ply INTEGER NOT NULL,
+    side TEXT NOT NULL,
+    uci TEXT NOT NULL,
+    san TEXT NOT NULL,
+    eval_before_cp INTEGER NOT NULL,
+    eval_after_cp INTEGER NOT NULL,
+    mate_in INTEGER,
+    best_move TEXT,
+    best_move_san TEXT,
+    cp_loss INTEGER NOT NULL,
+    classification TEXT NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE(game_id, ply),
+    FOREIGN KEY (game_id) REFERENCES games(id)
+);
+
+CREATE INDEX idx_game_analysis_game_id ON game_analysis(game_id);
+CREATE INDEX idx_games_analysis_status ON games(analysis_status);
@@ -0,0 +1,2 @@
+-- Engine playing against the human (see ENGINES_CONFIG)
+ALTER TABLE games ADD COLUMN engine TEXT NOT NULL DEFAULT 'stockfish';
@@ -0,0 +1,44 @@
+-- Bot strength settings, editable without recompiling. Profiles "1" to "20" back the
+-- difficulty levels; other names can be picked when creating a game.
+CREATE TABLE difficulty_profiles (
+    name TEXT PRIMARY KEY NOT NULL,
+    level INTEGER NOT NULL,
+    skill_level INTEGER NOT NULL,
+    movetime_ms INTEGER NOT NULL,
+    uci_elo INTEGER,
+    multipv INTEGER NOT NULL,
+    random_move_chance REAL NOT NULL,
+    blunder_chance REAL NOT NULL,
+    any_candidate_chance REAL NOT NULL,
+    weak_candidate_chance REAL NOT NULL,
+    near_best_chance REAL NOT NULL,
+    updated_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Same settings as the former hardcoded table
+INSERT INTO difficulty_profiles
+    (name, level, skill_level, movetime_ms, uci_elo, multipv, random_move_chance, blunder_chance, any_candidate_chance, weak_candidate_chance, near_best_chance)
+VALUES
+    ('1', 1, -20, 1, 100, 20, 0.95, 0.9, 0.95, 0.95, 0.95),
+    ('2', 2, -18, 1, 200, 20, 0.9, 0.8, 0.9, 0.9, 0.9),
+    ('3', 3, -15, 2, 300, 20, 0.85, 0.7, 0.85, 0.85, 0.85),
+    ('4', 4, -12, 5, 400, 20, 0.8, 0.0, 0.8, 0.8, 0.8),
+    ('5', 5, -10, 10, 500, 20, 0.75, 0.0, 0.75, 0.75, 0.75),
+    ('6', 6, -8, 25, 600, 20, 0.0, 0.0, 0.0, 0.4, 0.4),
+    ('7', 7, -5, 50, 700, 20, 0.0, 0.0, 0.0, 0.35, 0.35),
+    ('8', 8, -3, 75, 800, 20, 0.0, 0.0, 0.0, 0.3, 0.3),
+    ('9', 9, -1, 100, 900, 20, 0.0, 0.0, 0.0, 0.25, 0.25),
+    ('10', 10, 0, 150, 1000, 20, 0.0, 0.0, 0.0, 0.2, 0.2),
+    ('11', 11, 2, 200, NULL, 20, 0.0, 0.0, 0.0, 0.0, 0.15),
+    ('12', 12, 4, 300, NULL, 20, 0.0, 0.0, 0.0, 0.0, 0.12),
+    ('13', 13, 6, 400, NULL, 20, 0.0, 0.0, 0.0, 0.0, 0.1),
+    ('14', 14, 8, 500, NULL, 20, 0.0, 0.0, 0.0, 0.0, 0.08),
+    ('15', 15, 10, 750, NULL, 20, 0.0, 0.0, 0.0, 0.0, 0.06),
+    ('16', 16, 12, 450, NULL, 1, 0.0, 0.0, 0.0, 0.0, 0.0),
+    ('17', 17, 14, 350, NULL, 1, 0.0, 0.0, 0.0, 0.0, 0.0),
+    ('18', 18, 16, 300, NULL, 1, 0.0, 0.0, 0.0, 0.0, 0.0),
+    ('19', 19, 18, 250, NULL, 1, 0.0, 0.0, 0.0, 0.0, 0.0),
+    ('20', 20, 20, 200, NULL, 1, 0.0, 0.0, 0.0, 0.0, 0.0);
+
+-- Named profile picked for the game (None: the profile of its level)
+ALTER TABLE games ADD COLUMN difficulty_profile TEXT;
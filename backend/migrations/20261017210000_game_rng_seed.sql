@@ -0,0 +1,2 @@
+-- Seed of the bot's random choices, so any game can be replayed move for move
+ALTER TABLE games ADD COLUMN rng_seed INTEGER NOT NULL DEFAULT 0;
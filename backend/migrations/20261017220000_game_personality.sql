@@ -0,0 +1,2 @@
+-- Style of the bot's moves ("balanced", "aggressive", "positional", "defensive" or "trappy")
+ALTER TABLE games ADD COLUMN personality TEXT NOT NULL DEFAULT 'balanced';
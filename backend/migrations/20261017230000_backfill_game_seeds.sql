@@ -0,0 +1,3 @@
+-- Games created before seeds existed all got seed 0: give each one its own
+-- (within MAX_SEED, 2^53 - 1, like the seeds of new games)
+UPDATE games SET rng_seed = random() & 9007199254740991 WHERE rng_seed = 0;
@@ -0,0 +1,141 @@
+//! Fake UCI engine for tests: `STOCKFISH_PATH=target/debug/fake_uci`
+//!
+//! Every `go` plays the script file named by FAKE_UCI_SCRIPT (read again each time, so a
+//! test can change it between searches). Script lines are written to stdout as they are,
+//! except for these directives:
+//!
+//! - `sleep <ms>`: wait, still answering `isready` (a `stop` ends the wait)
+//! - `crash`: exit at once with status 1
+//! - `hang`: stop answering anything until killed
+//! - blank lines and lines starting with `#` are skipped
+//!
+//! After a `stop`, only the script's `bestmove` lines are still written. Without a script,
+//! the engine answers every `go` with the first legal move of the position.
+
+use std::io::{BufRead, Write};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+use chess_backend::services::ChessService;
+use chess_backend::services::chess_service::STARTING_FEN;
+
+enum Step {
+    Line(String),
+    Sleep(u64),
+    Crash,
+    Hang,
+}
+
+fn main() {
+    // Engine discovery runs `<path> --help`
+    if std::env::args().nth(1).as_deref() == Some("--help") {
+        println!("Fake UCI engine driven by FAKE_UCI_SCRIPT");
+        return;
+    }
+
+    // Commands are read on their own thread so a running script still sees `stop` and `isready`
+    let (sender, commands) = mpsc::channel::<String>();
+    std::thread::spawn(move || {
+        for line in std::io::stdin().lock().lines() {
+            let Ok(line) = line else { break };
+            if sender.send(line).is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut fen = STARTING_FEN.to_string();
+    while let Ok(command) = commands.recv() {
+        let command = command.trim();
+        match command.split_whitespace().next() {
+            Some("uci") => {
+                say("id name Fake UCI");
+                say("id author chess-backend tests");
+                say("uciok");
+            }
+            Some("isready") => say("readyok"),
+            Some("position") => fen = position_fen(command),
+            Some("go") => search(&fen, &commands),
+            Some("quit") => return,
+            // setoption, ucinewgame, stop outside a search...
+            _ => {}
+        }
+    }
+}
+
+/// FEN of a `position fen ...` or `position startpos` command (moves are not supported)
+fn position_fen(command: &str) -> String {
+    match command.split_once(" fen ") {
+        Some((_, rest)) => rest.split(" moves ").next().unwrap_or(rest).trim().to_string(),
+        None => STARTING_FEN.to_string(),
+    }
+}
+
+fn search(fen: &str, commands: &Receiver<String>) {
+    let Some(script) = load_script() else {
+        let best_move = ChessService::get_legal_moves(fen)
+            .ok()
+            .and_then(|moves| moves.into_iter().next())
+            .unwrap_or_else(|| "(none)".to_string());
+        say(&format!("bestmove {}", best_move));
+        return;
+    };
+
+    let mut stopped = false;
+    for step in script {
+        match step {
+            Step::Line(line) => {
+                if !stopped || line.starts_with("bestmove") {
+                    say(&line);
+                }
+            }
+            Step::Sleep(ms) if !stopped => stopped = wait(Duration::from_millis(ms), commands),
+            Step::Sleep(_) => {}
+            Step::Crash => std::process::exit(1),
+            Step::Hang => loop {
+                std::thread::sleep(Duration::from_secs(60));
+            },
+        }
+    }
+}
+
+/// Waits while answering `isready`; returns true when `stop` arrived
+fn wait(duration: Duration, commands: &Receiver<String>) -> bool {
+    let deadline = Instant::now() + duration;
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        match commands.recv_timeout(remaining) {
+            Ok(command) => match command.trim() {
+                "isready" => say("readyok"),
+                "stop" => return true,
+                "quit" => std::process::exit(0),
+                _ => {}
+            },
+            Err(RecvTimeoutError::Timeout) => return false,
+            Err(RecvTimeoutError::Disconnected) => std::process::exit(0),
+        }
+    }
+}
+
+fn load_script() -> Option<Vec<Step>> {
+    let path = std::env::var("FAKE_UCI_SCRIPT").ok()?;
+    let text = std::fs::read_to_string(path).ok()?;
+    let steps = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| match line.split_once(' ') {
+            Some(("sleep", ms)) => Step::Sleep(ms.trim().parse().unwrap_or(0)),
+            _ if line == "crash" => Step::Crash,
+            _ if line == "hang" => Step::Hang,
+            _ => Step::Line(line.to_string()),
+        })
+        .collect();
+    Some(steps)
+}
+
+fn say(line: &str) {
+    let mut stdout = std::io::stdout().lock();
+    // The engine's reader may be gone (e.g., the process is being killed)
+    let _ = writeln!(stdout, "{}", line);
+    let _ = stdout.flush();
+}
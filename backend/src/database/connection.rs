@@ -1,5 +1,6 @@
-use sqlx::{SqlitePool, Row};
-use crate::models::{User, Game};
+use sqlx::{Executor, Row, Sqlite, SqlitePool};
+use sqlx::sqlite::SqliteRow;
+use crate::models::{User, Game, GameMove, AnalysisStatus, MoveAnalysis, DifficultyProfile};
 
 pub async fn get_user_by_id(pool: &SqlitePool, user_id: &str) -> Result<Option<User>, sqlx::Error> {
     let row = sqlx::query("SELECT * FROM users WHERE id = ?")
@@ -52,64 +53,377 @@ pub async fn get_game_by_id(pool: &SqlitePool, game_id: &str) -> Result<Option<G
         .fetch_optional(pool)
         .await?;
 
-    if let Some(row) = row {
-        Ok(Some(Game {
-            id: row.get("id"),
-            user_id: row.get("user_id"),
-            difficulty: row.get::<i64, _>("difficulty") as i32,
-            fen: row.get("fen"),
-            status: row.get("status"),
-            result: row.get("result"),
-            created_at: row.get("created_at"),
-            start_time: row.get("start_time"),
-            end_time: row.get("end_time"),
-            duration_seconds: row.get::<Option<i64>, _>("duration_seconds").map(|v| v as i32),
-            moves_count: row.get::<i64, _>("moves_count") as i32,
-        }))
-    } else {
-        Ok(None)
-    }
+    Ok(row.map(|row| game_from_row(&row)))
 }
 
-pub async fn update_game(pool: &SqlitePool, game: &Game) -> Result<(), sqlx::Error> {
-    sqlx::query!(
-        "UPDATE games SET fen = ?, status = ?, result = ?, end_time = ?, duration_seconds = ?, moves_count = ? WHERE id = ?",
-        game.fen,
-        game.status,
-        game.result,
-        game.end_time,
-        game.duration_seconds,
-        game.moves_count,
-        game.id
+/// Stores a new game (`executor`: the pool or a transaction)
+pub async fn insert_game<'e, E: Executor<'e, Database = Sqlite>>(executor: E, game: &Game) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO games (id, user_id, difficulty, fen, status, result, created_at, start_time, end_time, \
+         duration_seconds, moves_count, draw_reason, position_history, player_color, start_fen, variant, termination, takebacks, takebacks_allowed, hints_used, analysis_status, engine, difficulty_profile, rng_seed, personality, \
+         clock_base_ms, clock_increment_ms, clock_per_move, white_clock_ms, black_clock_ms, turn_started_at) \
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
     )
-    .execute(pool)
+    .bind(&game.id)
+    .bind(&game.user_id)
+    .bind(game.difficulty)
+    .bind(&game.fen)
+    .bind(&game.status)
+    .bind(&game.result)
+    .bind(game.created_at)
+    .bind(game.start_time)
+    .bind(game.end_time)
+    .bind(game.duration_seconds)
+    .bind(game.moves_count)
+    .bind(&game.draw_reason)
+    .bind(&game.position_history)
+    .bind(&game.player_color)
+    .bind(&game.start_fen)
+    .bind(&game.variant)
+    .bind(game.termination)
+    .bind(game.takebacks)
+    .bind(game.takebacks_allowed)
+    .bind(game.hints_used)
+    .bind(game.analysis_status)
+    .bind(&game.engine)
+    .bind(&game.difficulty_profile)
+    .bind(game.rng_seed)
+    .bind(game.personality)
+    .bind(game.clock_base_ms)
+    .bind(game.clock_increment_ms)
+    .bind(game.clock_per_move)
+    .bind(game.white_clock_ms)
+    .bind(game.black_clock_ms)
+    .bind(game.turn_started_at)
+    .execute(executor)
     .await?;
 
     Ok(())
 }
 
+/// UPDATE of every column a game changes while it is played, with extra WHERE conditions
+macro_rules! update_game_sql {
+    ($condition:literal) => {
+        concat!(
+            "UPDATE games SET fen = ?, status = ?, result = ?, end_time = ?, duration_seconds = ?, moves_count = ?, \
+             draw_reason = ?, position_history = ?, termination = ?, takebacks = ?, hints_used = ?, analysis_status = ?, \
+             white_clock_ms = ?, black_clock_ms = ?, turn_started_at = ? WHERE id = ?",
+            $condition
+        )
+    };
+}
+
+/// Binds the values of `update_game_sql!` (the game's id last)
+fn bind_game_update<'q>(
+    query: sqlx::query::Query<'q, Sqlite, sqlx::sqlite::SqliteArguments<'q>>,
+    game: &'q Game,
+) -> sqlx::query::Query<'q, Sqlite, sqlx::sqlite::SqliteArguments<'q>> {
+    query
+        .bind(&game.fen)
+        .bind(&game.status)
+        .bind(&game.result)
+        .bind(game.end_time)
+        .bind(game.duration_seconds)
+        .bind(game.moves_count)
+        .bind(&game.draw_reason)
+        .bind(&game.position_history)
+        .bind(game.termination)
+        .bind(game.takebacks)
+        .bind(game.hints_used)
+        .bind(game.analysis_status)
+        .bind(game.white_clock_ms)
+        .bind(game.black_clock_ms)
+        .bind(game.turn_started_at)
+        .bind(&game.id)
+}
+
+/// Saves a game (`executor`: the pool or a transaction)
+pub async fn update_game<'e, E: Executor<'e, Database = Sqlite>>(executor: E, game: &Game) -> Result<(), sqlx::Error> {
+    bind_game_update(sqlx::query(update_game_sql!("")), game)
+        .execute(executor)
+        .await?;
+
+    Ok(())
+}
+
+/// Saves a game only if it is still active with `moves_count` plies in the database
+/// (nobody moved, took back or ended it meanwhile)
+///
+/// # Returns
+/// Result<bool, sqlx::Error> - Whether the game was saved
+pub async fn update_game_from_ply<'e, E: Executor<'e, Database = Sqlite>>(
+    executor: E,
+    game: &Game,
+    moves_count: i32,
+) -> Result<bool, sqlx::Error> {
+    let result = bind_game_update(sqlx::query(update_game_sql!(" AND moves_count = ? AND status = 'active'")), game)
+        .bind(moves_count)
+        .execute(executor)
+        .await?;
+
+    Ok(result.rows_affected() == 1)
+}
+
 pub async fn get_games_by_user(pool: &SqlitePool, user_id: &str) -> Result<Vec<Game>, sqlx::Error> {
     let rows = sqlx::query("SELECT * FROM games WHERE user_id = ? ORDER BY created_at DESC")
         .bind(user_id)
         .fetch_all(pool)
         .await?;
 
-    let mut games = Vec::new();
-    for row in rows {
-        games.push(Game {
+    Ok(rows.iter().map(game_from_row).collect())
+}
+
+/// Maps a `games` row to a Game (SQL integers are i64, cast to i32 explicitly)
+fn game_from_row(row: &SqliteRow) -> Game {
+    Game {
+        id: row.get("id"),
+        user_id: row.get("user_id"),
+        difficulty: row.get::<i64, _>("difficulty") as i32,
+        fen: row.get("fen"),
+        status: row.get("status"),
+        result: row.get("result"),
+        created_at: row.get("created_at"),
+        start_time: row.get("start_time"),
+        end_time: row.get("end_time"),
+        duration_seconds: row.get::<Option<i64>, _>("duration_seconds").map(|v| v as i32),
+        moves_count: row.get::<i64, _>("moves_count") as i32,
+        draw_reason: row.get("draw_reason"),
+        position_history: row.get("position_history"),
+        player_color: row.get("player_color"),
+        start_fen: row.get("start_fen"),
+        variant: row.get("variant"),
+        termination: row.get("termination"),
+        takebacks: row.get::<i64, _>("takebacks") as i32,
+        takebacks_allowed: row.get("takebacks_allowed"),
+        hints_used: row.get::<i64, _>("hints_used") as i32,
+        analysis_status: row.get("analysis_status"),
+        engine: row.get("engine"),
+        difficulty_profile: row.get("difficulty_profile"),
+        rng_seed: row.get("rng_seed"),
+        personality: row.get("personality"),
+        clock_base_ms: row.get("clock_base_ms"),
+        clock_increment_ms: row.get("clock_increment_ms"),
+        clock_per_move: row.get("clock_per_move"),
+        white_clock_ms: row.get("white_clock_ms"),
+        black_clock_ms: row.get("black_clock_ms"),
+        turn_started_at: row.get("turn_started_at"),
+    }
+}
+
+/// Stores one move of a game's history (`executor`: the pool or a transaction)
+pub async fn insert_game_move<'e, E: Executor<'e, Database = Sqlite>>(executor: E, game_move: &GameMove) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO game_moves (id, game_id, ply, side, uci, san, fen_after, think_time_ms, created_at) \
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+    )
+    .bind(&game_move.id)
+    .bind(&game_move.game_id)
+    .bind(game_move.ply)
+    .bind(&game_move.side)
+    .bind(&game_move.uci)
+    .bind(&game_move.san)
+    .bind(&game_move.fen_after)
+    .bind(game_move.think_time_ms)
+    .bind(game_move.created_at)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+/// Active games played with a clock (checked by the flag-fall sweeper)
+pub async fn get_active_timed_games(pool: &SqlitePool) -> Result<Vec<Game>, sqlx::Error> {
+    let rows = sqlx::query("SELECT * FROM games WHERE status = 'active' AND clock_base_ms IS NOT NULL")
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows.iter().map(game_from_row).collect())
+}
+
+/// Deletes the moves of a game from the given ply onwards (takebacks)
+pub async fn delete_game_moves_from<'e, E: Executor<'e, Database = Sqlite>>(executor: E, game_id: &str, ply: i32) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM game_moves WHERE game_id = ? AND ply >= ?")
+        .bind(game_id)
+        .bind(ply)
+        .execute(executor)
+        .await?;
+
+    Ok(())
+}
+
+pub async fn get_game_moves(pool: &SqlitePool, game_id: &str) -> Result<Vec<GameMove>, sqlx::Error> {
+    let rows = sqlx::query("SELECT * FROM game_moves WHERE game_id = ? ORDER BY ply ASC")
+        .bind(game_id)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| GameMove {
             id: row.get("id"),
-            user_id: row.get("user_id"),
-            difficulty: row.get::<i64, _>("difficulty") as i32,
-            fen: row.get("fen"),
-            status: row.get("status"),
-            result: row.get("result"),
+            game_id: row.get("game_id"),
+            ply: row.get::<i64, _>("ply") as i32,
+            side: row.get("side"),
+            uci: row.get("uci"),
+            san: row.get("san"),
+            fen_after: row.get("fen_after"),
+            think_time_ms: row.get::<Option<i64>, _>("think_time_ms").map(|v| v as i32),
             created_at: row.get("created_at"),
-            start_time: row.get("start_time"),
-            end_time: row.get("end_time"),
-            duration_seconds: row.get::<Option<i64>, _>("duration_seconds").map(|v| v as i32),
-            moves_count: row.get::<i64, _>("moves_count") as i32,
-        });
+        })
+        .collect())
+}
+
+/// Sets the post-game analysis progress of a game without touching the rest of the row
+pub async fn set_analysis_status(pool: &SqlitePool, game_id: &str, status: AnalysisStatus) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE games SET analysis_status = ? WHERE id = ?")
+        .bind(status)
+        .bind(game_id)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+/// Oldest finished game waiting for its post-game analysis
+pub async fn get_next_pending_analysis(pool: &SqlitePool) -> Result<Option<Game>, sqlx::Error> {
+    let row = sqlx::query("SELECT * FROM games WHERE analysis_status = 'pending' ORDER BY end_time ASC LIMIT 1")
+        .fetch_optional(pool)
+        .await?;
+
+    Ok(row.map(|row| game_from_row(&row)))
+}
+
+/// Puts analyses interrupted by a restart back in the queue
+pub async fn requeue_running_analyses(pool: &SqlitePool) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("UPDATE games SET analysis_status = 'pending' WHERE analysis_status = 'running'")
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected())
+}
+
+/// Replaces the stored analysis of a game
+pub async fn replace_game_analysis(pool: &SqlitePool, game_id: &str, moves: &[MoveAnalysis]) -> Result<(), sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("DELETE FROM game_analysis WHERE game_id = ?")
+        .bind(game_id)
+        .execute(&mut *tx)
+        .await?;
+
+    for analysis in moves {
+        sqlx::query(
+            "INSERT INTO game_analysis (id, game_id, ply, side, uci, san, eval_before_cp, eval_after_cp, mate_in, \
+             best_move, best_move_san, cp_loss, classification, created_at) \
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+        )
+        .bind(&analysis.id)
+        .bind(&analysis.game_id)
+        .bind(analysis.ply)
+        .bind(&analysis.side)
+        .bind(&analysis.uci)
+        .bind(&analysis.san)
+        .bind(analysis.eval_before_cp)
+        .bind(analysis.eval_after_cp)
+        .bind(analysis.mate_in)
+        .bind(&analysis.best_move)
+        .bind(&analysis.best_move_san)
+        .bind(analysis.cp_loss)
+        .bind(analysis.classification)
+        .bind(analysis.created_at)
+        .execute(&mut *tx)
+        .await?;
     }
 
-    Ok(games)
-}
\ No newline at end of file
+    tx.commit().await
+}
+
+pub async fn get_game_analysis(pool: &SqlitePool, game_id: &str) -> Result<Vec<MoveAnalysis>, sqlx::Error> {
+    let rows = sqlx::query("SELECT * FROM game_analysis WHERE game_id = ? ORDER BY ply ASC")
+        .bind(game_id)
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| MoveAnalysis {
+            id: row.get("id"),
+            game_id: row.get("game_id"),
+            ply: row.get::<i64, _>("ply") as i32,
+            side: row.get("side"),
+            uci: row.get("uci"),
+            san: row.get("san"),
+            eval_before_cp: row.get::<i64, _>("eval_before_cp") as i32,
+            eval_after_cp: row.get::<i64, _>("eval_after_cp") as i32,
+            mate_in: row.get::<Option<i64>, _>("mate_in").map(|v| v as i32),
+            best_move: row.get("best_move"),
+            best_move_san: row.get("best_move_san"),
+            cp_loss: row.get::<i64, _>("cp_loss") as i32,
+            classification: row.get("classification"),
+            created_at: row.get("created_at"),
+        })
+        .collect())
+}
+
+pub async fn get_difficulty_profiles(pool: &SqlitePool) -> Result<Vec<DifficultyProfile>, sqlx::Error> {
+    let rows = sqlx::query("SELECT * FROM difficulty_profiles ORDER BY level ASC, name ASC")
+        .fetch_all(pool)
+        .await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| DifficultyProfile {
+            name: row.get("name"),
+            level: row.get::<i64, _>("level") as i32,
+            skill_level: row.get::<i64, _>("skill_level") as i32,
+            movetime_ms: row.get::<i64, _>("movetime_ms") as i32,
+            uci_elo: row.get::<Option<i64>, _>("uci_elo").map(|v| v as i32),
+            multipv: row.get::<i64, _>("multipv") as i32,
+            random_move_chance: row.get("random_move_chance"),
+            blunder_chance: row.get("blunder_chance"),
+            any_candidate_chance: row.get("any_candidate_chance"),
+            weak_candidate_chance: row.get("weak_candidate_chance"),
+            near_best_chance: row.get("near_best_chance"),
+            updated_at: row.get("updated_at"),
+        })
+        .collect())
+}
+
+/// Creates a difficulty profile or replaces the one with the same name
+pub async fn upsert_difficulty_profile(pool: &SqlitePool, profile: &DifficultyProfile) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO difficulty_profiles (name, level, skill_level, movetime_ms, uci_elo, multipv, random_move_chance, \
+         blunder_chance, any_candidate_chance, weak_candidate_chance, near_best_chance, updated_at) \
+         VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?) \
+         ON CONFLICT(name) DO UPDATE SET level = excluded.level, skill_level = excluded.skill_level, \
+         movetime_ms = excluded.movetime_ms, uci_elo = excluded.uci_elo, multipv = excluded.multipv, \
+         random_move_chance = excluded.random_move_chance, blunder_chance = excluded.blunder_chance, \
+         any_candidate_chance = excluded.any_candidate_chance, weak_candidate_chance = excluded.weak_candidate_chance, \
+         near_best_chance = excluded.near_best_chance, updated_at = excluded.updated_at",
+    )
+    .bind(&profile.name)
+    .bind(profile.level)
+    .bind(profile.skill_level)
+    .bind(profile.movetime_ms)
+    .bind(profile.uci_elo)
+    .bind(profile.multipv)
+    .bind(profile.random_move_chance)
+    .bind(profile.blunder_chance)
+    .bind(profile.any_candidate_chance)
+    .bind(profile.weak_candidate_chance)
+    .bind(profile.near_best_chance)
+    .bind(profile.updated_at)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+/// Deletes a difficulty profile, returning whether it existed
+pub async fn delete_difficulty_profile(pool: &SqlitePool, name: &str) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM difficulty_profiles WHERE name = ?")
+        .bind(name)
+        .execute(pool)
+        .await?;
+
+    Ok(result.rows_affected() > 0)
+}
@@ -1,3 +1,3 @@
 pub mod schema;
 
-pub use schema::{QueryRoot, MutationRoot};
\ No newline at end of file
+pub use schema::{QueryRoot, MutationRoot, AdminToken};
\ No newline at end of file
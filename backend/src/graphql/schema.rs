@@ -1,13 +1,28 @@
 use async_graphql::*;
-use crate::models::{User, Game, NewGameInput, MakeMoveInput, GameMoveResult, UserProfile}; // Ajouter UserProfile
+use crate::models::{User, Game, GameMove, DifficultyProfile, DifficultyProfileInput, LegalMoves, Evaluation, AnalysisInput, PositionAnalysis, GameAnalysis, DrawOfferResult, Hint, NewGameInput, ImportPgnInput, MakeMoveInput, GameMoveResult, UserProfile}; // Ajouter UserProfile
 use sqlx::SqlitePool;
-use crate::services::{UserService, GameService, StatsService};
+use crate::services::{UserService, GameService, StatsService, PgnService, ClockService, ChessService, AnalysisService, EngineRegistry, DifficultyProfiles, DifficultyService};
 use crate::database::*;
 
 
 /// GraphQL Query root - handles all read operations
 pub struct QueryRoot;
 
+/// Token sent in the request's X-Admin-Token header, if any
+pub struct AdminToken(pub String);
+
+/// Allows admin operations only with the token set in ADMIN_TOKEN (never when it is unset)
+fn require_admin(ctx: &Context<'_>) -> Result<(), Error> {
+    let expected = std::env::var("ADMIN_TOKEN")
+        .ok()
+        .filter(|token| !token.is_empty())
+        .ok_or_else(|| Error::new("Admin operations are disabled (ADMIN_TOKEN is not set)"))?;
+    match ctx.data_opt::<AdminToken>() {
+        Some(token) if token.0 == expected => Ok(()),
+        _ => Err(Error::new("Invalid admin token")),
+    }
+}
+
 #[Object]
 impl QueryRoot {
     /// Retrieves a user by their ID
@@ -110,6 +125,46 @@ impl QueryRoot {
         Ok(games)
     }
 
+    /// Legal moves of a game's current position, grouped by origin square
+    async fn legal_moves(&self, ctx: &Context<'_>, game_id: String) -> Result<LegalMoves, Error> {
+        let db = ctx.data::<SqlitePool>()?;
+        let game = GameService::get_game(db, &game_id)
+            .await?
+            .ok_or_else(|| Error::new("Game not found"))?;
+        let moves = ChessService::legal_moves_by_square(&game.fen)?;
+        Ok(moves)
+    }
+
+    /// Legal moves of any position, grouped by origin square
+    async fn legal_moves_for_fen(&self, fen: String) -> Result<LegalMoves, Error> {
+        let fen = ChessService::validate_fen(&fen, ChessService::is_chess960_fen(&fen))?;
+        let moves = ChessService::legal_moves_by_square(&fen)?;
+        Ok(moves)
+    }
+
+    /// Full-strength engine evaluation of a position, from White's point of view
+    async fn evaluate(&self, fen: String, depth: Option<i32>) -> Result<Evaluation, Error> {
+        let evaluation = AnalysisService::evaluate(&fen, depth).await?;
+        Ok(evaluation)
+    }
+
+    /// Top engine lines (Multi-PV) of a FEN or of a game position, at full strength
+    async fn analyse_position(&self, ctx: &Context<'_>, input: AnalysisInput) -> Result<PositionAnalysis, Error> {
+        let db = ctx.data::<SqlitePool>()?;
+        let analysis = AnalysisService::analyse_position(db, input).await?;
+        Ok(analysis)
+    }
+
+    /// Bots players can choose when creating a game
+    async fn bots(&self) -> Vec<String> {
+        EngineRegistry::global().bot_names()
+    }
+
+    /// Difficulty profiles, by level then name ("1" to "20" back the difficulty levels)
+    async fn difficulty_profiles(&self) -> Vec<DifficultyProfile> {
+        DifficultyProfiles::global().all()
+    }
+
     /// Simple health check endpoint
     async fn hello(&self) -> &str {
         "Hello from Chess GraphQL API!"
@@ -165,6 +220,14 @@ impl MutationRoot {
         Ok(game)
     }
 
+    /// Imports one or more games from PGN text
+    /// Each game is replayed and validated move by move; unfinished games stay active
+    async fn import_pgn(&self, ctx: &Context<'_>, input: ImportPgnInput) -> Result<Vec<Game>, Error> {
+        let db = ctx.data::<SqlitePool>()?;
+        let games = GameService::import_pgn(db, input).await?;
+        Ok(games)
+    }
+
     /// Makes a move in an existing game
     /// Validates the move, applies it, and gets Stockfish response
     /// Updates game statistics if game ends
@@ -173,4 +236,116 @@ impl MutationRoot {
         let result = GameService::make_move(db, input).await?;
         Ok(result)
     }
-}
\ No newline at end of file
+
+    /// Takes back the player's last move and the engine's reply
+    async fn take_back(&self, ctx: &Context<'_>, game_id: String) -> Result<Game, Error> {
+        let db = ctx.data::<SqlitePool>()?;
+        let game = GameService::take_back(db, &game_id).await?;
+        Ok(game)
+    }
+
+    /// Suggests the best move with a full-strength search (counted on the game)
+    async fn get_hint(&self, ctx: &Context<'_>, game_id: String) -> Result<Hint, Error> {
+        let db = ctx.data::<SqlitePool>()?;
+        let hint = GameService::get_hint(db, &game_id).await?;
+        Ok(hint)
+    }
+
+    /// Queues a finished game for post-game analysis (e.g., an imported game)
+    async fn analyse_game(&self, ctx: &Context<'_>, game_id: String) -> Result<Game, Error> {
+        let db = ctx.data::<SqlitePool>()?;
+        let game = AnalysisService::request_game_analysis(db, &game_id).await?;
+        Ok(game)
+    }
+
+    /// Creates or replaces a difficulty profile (admin); games use it from their next move
+    async fn save_difficulty_profile(&self, ctx: &Context<'_>, input: DifficultyProfileInput) -> Result<DifficultyProfile, Error> {
+        require_admin(ctx)?;
+        let db = ctx.data::<SqlitePool>()?;
+        let profile = DifficultyService::save_profile(db, input).await?;
+        Ok(profile)
+    }
+
+    /// Deletes a named difficulty profile (admin); its games fall back to their level's profile
+    async fn delete_difficulty_profile(&self, ctx: &Context<'_>, name: String) -> Result<bool, Error> {
+        require_admin(ctx)?;
+        let db = ctx.data::<SqlitePool>()?;
+        let deleted = DifficultyService::delete_profile(db, &name).await?;
+        Ok(deleted)
+    }
+
+    /// Reloads the difficulty profiles after the table was edited directly (admin)
+    async fn reload_difficulty_profiles(&self, ctx: &Context<'_>) -> Result<Vec<DifficultyProfile>, Error> {
+        require_admin(ctx)?;
+        let db = ctx.data::<SqlitePool>()?;
+        DifficultyProfiles::reload(db).await?;
+        Ok(DifficultyProfiles::global().all())
+    }
+
+    /// Resigns an active game, the engine wins
+    async fn resign_game(&self, ctx: &Context<'_>, game_id: String) -> Result<Game, Error> {
+        let db = ctx.data::<SqlitePool>()?;
+        let game = GameService::resign_game(db, &game_id).await?;
+        Ok(game)
+    }
+
+    /// Aborts a game during its first plies
+    /// Aborted games have no result and are not counted in statistics
+    async fn abort_game(&self, ctx: &Context<'_>, game_id: String) -> Result<Game, Error> {
+        let db = ctx.data::<SqlitePool>()?;
+        let game = GameService::abort_game(db, &game_id).await?;
+        Ok(game)
+    }
+
+    /// Offers a draw to the engine
+    /// The engine accepts depending on its evaluation of the position and its level
+    async fn offer_draw(&self, ctx: &Context<'_>, game_id: String) -> Result<DrawOfferResult, Error> {
+        let db = ctx.data::<SqlitePool>()?;
+        let result = GameService::offer_draw(db, &game_id).await?;
+        Ok(result)
+    }
+}
+
+/// Resolvers for Game fields that are not stored on the `games` row
+#[ComplexObject]
+impl Game {
+    /// Every move played in this game, in order
+    async fn moves(&self, ctx: &Context<'_>) -> Result<Vec<GameMove>, Error> {
+        let db = ctx.data::<SqlitePool>()?;
+        let moves = GameService::get_game_moves(db, &self.id).await?;
+        Ok(moves)
+    }
+
+    /// Time control label ("5+3", "2 days per move"), None for untimed games
+    async fn time_control(&self) -> Option<String> {
+        ClockService::label(self)
+    }
+
+    /// White's remaining time right now, including the move in progress
+    async fn white_time_left_ms(&self) -> Option<i64> {
+        ClockService::remaining_ms(self, "white", chrono::Utc::now()).map(|ms| ms.max(0))
+    }
+
+    /// Black's remaining time right now, including the move in progress
+    async fn black_time_left_ms(&self) -> Option<i64> {
+        ClockService::remaining_ms(self, "black", chrono::Utc::now()).map(|ms| ms.max(0))
+    }
+
+    /// Post-game engine analysis (None when no analysis was requested)
+    async fn analysis(&self, ctx: &Context<'_>) -> Result<Option<GameAnalysis>, Error> {
+        let db = ctx.data::<SqlitePool>()?;
+        let analysis = AnalysisService::get_game_analysis(db, self).await?;
+        Ok(analysis)
+    }
+
+    /// The game in Portable Game Notation
+    async fn pgn(&self, ctx: &Context<'_>) -> Result<String, Error> {
+        let db = ctx.data::<SqlitePool>()?;
+        let moves = GameService::get_game_moves(db, &self.id).await?;
+        let username = get_user_by_id(db, &self.user_id)
+            .await?
+            .map(|user| user.username)
+            .unwrap_or_else(|| "?".to_string());
+        Ok(PgnService::to_pgn(self, &moves, &username))
+    }
+}
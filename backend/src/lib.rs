@@ -0,0 +1,4 @@
+pub mod database;
+pub mod graphql;
+pub mod models;
+pub mod services;
@@ -1,12 +1,7 @@
-mod database;
-mod graphql;
-mod models;
-mod services;
-
 use axum::{
-    extract::Extension,
-    http::{HeaderValue, Method},
-    response::Html,
+    extract::{Extension, Path},
+    http::{header, HeaderMap, HeaderValue, Method, StatusCode},
+    response::{Html, IntoResponse},
     routing::{get, post},
     Router,
 };
@@ -20,10 +15,18 @@ use tower_http::cors::{Any, CorsLayer};
 use std::env;
 use tracing::{error, info, warn};
 use tracing_subscriber::{self, EnvFilter};
-use graphql::{QueryRoot, MutationRoot};
+use chess_backend::database;
+use chess_backend::graphql::{AdminToken, QueryRoot, MutationRoot};
+use chess_backend::services::{AnalysisService, CalibrationService, DifficultyProfiles, EngineRegistry, GameService, PgnService};
 use std::fs::{OpenOptions};
 use std::io::Write;
 
+/// How often active timed games are checked for a fallen flag
+const CLOCK_SWEEP_INTERVAL_SECS: u64 = 5;
+
+/// How often the queue of finished games waiting for analysis is checked
+const ANALYSIS_POLL_INTERVAL_SECS: u64 = 5;
+
 /// Lightweight health probe
 async fn healthz() -> &'static str { "ok" }
 
@@ -105,6 +108,63 @@ async fn main() {
     }
     info!("✅ Migrations applied");
 
+    // Difficulty profiles (reloaded whenever an admin edits them)
+    if let Err(e) = DifficultyProfiles::reload(&pool).await {
+        error!("❌ Failed to load difficulty profiles: {}", e);
+        std::process::exit(1);
+    }
+
+    // Engines configured by ENGINES_CONFIG (Stockfish and the built-in engine otherwise)
+    if let Err(e) = EngineRegistry::load_from_env() {
+        error!("❌ {}", e);
+        std::process::exit(1);
+    }
+
+    // `chess-backend calibrate [options]` plays engine-vs-engine games instead of serving
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("calibrate") {
+        match CalibrationService::run_cli(&args[1..]).await {
+            Ok(()) => std::process::exit(0),
+            Err(e) => {
+                error!("❌ Calibration failed: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    // Background sweeper: games lost on time even if the player never moves again
+    let sweeper_pool = pool.clone();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(CLOCK_SWEEP_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            match GameService::flag_expired_games(&sweeper_pool).await {
+                Ok(0) => {}
+                Ok(flagged) => info!("⏱️ {} game(s) lost on time", flagged),
+                Err(e) => warn!("⚠️ Clock sweep failed: {}", e),
+            }
+        }
+    });
+
+    // Background analysis worker: finished games are analysed one at a time
+    let analysis_pool = pool.clone();
+    tokio::spawn(async move {
+        match database::requeue_running_analyses(&analysis_pool).await {
+            Ok(0) => {}
+            Ok(requeued) => info!("🔬 {} interrupted analysis(es) queued again", requeued),
+            Err(e) => warn!("⚠️ Could not requeue interrupted analyses: {}", e),
+        }
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(ANALYSIS_POLL_INTERVAL_SECS));
+        loop {
+            interval.tick().await;
+            match AnalysisService::run_pending_analyses(&analysis_pool).await {
+                Ok(0) => {}
+                Ok(analysed) => info!("🔬 {} game(s) analysed", analysed),
+                Err(e) => warn!("⚠️ Game analysis failed: {}", e),
+            }
+        }
+    });
+
     // Create GraphQL schema
     info!("🔧 Building GraphQL schema...");
     let schema = Schema::build(QueryRoot, MutationRoot, EmptySubscription)
@@ -125,7 +185,9 @@ async fn main() {
         .route("/", get(graphiql))
         .route("/graphql", post(graphql_handler))
         .route("/healthz", get(healthz))
+        .route("/games/{file}", get(game_pgn))
         .layer(Extension(schema))
+        .layer(Extension(pool))
         .layer(cors);
 
     // Start server
@@ -165,10 +227,41 @@ async fn graphiql() -> Html<String> {
     Html(GraphiQLSource::build().endpoint("/graphql").finish())
 }
 
-/// Handles GraphQL requests
+/// Handles GraphQL requests (the X-Admin-Token header unlocks admin mutations)
 async fn graphql_handler(
     schema: Extension<Schema<QueryRoot, MutationRoot, EmptySubscription>>,
+    headers: HeaderMap,
     req: GraphQLRequest,
 ) -> GraphQLResponse {
-    schema.execute(req.into_inner()).await.into()
+    let mut req = req.into_inner();
+    if let Some(token) = headers.get("x-admin-token").and_then(|value| value.to_str().ok()) {
+        req = req.data(AdminToken(token.to_string()));
+    }
+    schema.execute(req).await.into()
+}
+
+/// Downloads a game as a PGN file (`/games/{id}.pgn`)
+async fn game_pgn(
+    Extension(pool): Extension<SqlitePool>,
+    Path(file): Path<String>,
+) -> impl IntoResponse {
+    let Some(game_id) = file.strip_suffix(".pgn") else {
+        return (StatusCode::NOT_FOUND, "Not found").into_response();
+    };
+
+    match PgnService::export_game(&pool, game_id).await {
+        Ok(Some(pgn)) => (
+            [
+                (header::CONTENT_TYPE, "application/x-chess-pgn".to_string()),
+                (header::CONTENT_DISPOSITION, format!("attachment; filename=\"game-{}.pgn\"", game_id)),
+            ],
+            pgn,
+        )
+            .into_response(),
+        Ok(None) => (StatusCode::NOT_FOUND, "Game not found").into_response(),
+        Err(e) => {
+            error!("❌ PGN export failed: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "PGN export failed").into_response()
+        }
+    }
 }
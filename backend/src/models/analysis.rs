@@ -0,0 +1,68 @@
+use async_graphql::*;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use chrono::{DateTime, Utc};
+
+/// Progress of a game's post-game analysis
+#[derive(Enum, sqlx::Type, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum AnalysisStatus {
+    Pending,
+    Running,
+    Done,
+    Failed,
+}
+
+/// Quality of a move, from the centipawns it lost compared to the engine's best move
+#[derive(Enum, sqlx::Type, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum MoveClassification {
+    Best,
+    Good,
+    Inaccuracy,
+    Mistake,
+    Blunder,
+}
+
+/// Engine verdict on one ply of a finished game
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, SimpleObject)]
+pub struct MoveAnalysis {
+    #[graphql(skip)]
+    pub id: String,
+    #[graphql(skip)]
+    pub game_id: String,
+    /// Half-move number, starting at 1
+    pub ply: i32,
+    /// Side that played the move ("white" or "black")
+    pub side: String,
+    pub uci: String,
+    pub san: String,
+    /// Evaluation before the move, in centipawns from White's point of view (mates count as ±100000)
+    pub eval_before_cp: i32,
+    /// Evaluation after the move, in centipawns from White's point of view
+    pub eval_after_cp: i32,
+    /// Moves until mate after the move, from White's point of view (negative when Black mates)
+    pub mate_in: Option<i32>,
+    /// Engine's best move in the position before the move (None when it found none)
+    pub best_move: Option<String>,
+    pub best_move_san: Option<String>,
+    /// Centipawns lost by the move compared to the best move (0 for the best move)
+    pub cp_loss: i32,
+    pub classification: MoveClassification,
+    #[graphql(skip)]
+    pub created_at: DateTime<Utc>,
+}
+
+/// Post-game analysis of a game
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct GameAnalysis {
+    pub status: AnalysisStatus,
+    /// Average accuracy of White's moves, 0 to 100 (None until the analysis is done)
+    pub white_accuracy: Option<f64>,
+    /// Average accuracy of Black's moves, 0 to 100 (None until the analysis is done)
+    pub black_accuracy: Option<f64>,
+    /// Analysed plies in order (empty until the analysis is done)
+    pub moves: Vec<MoveAnalysis>,
+}
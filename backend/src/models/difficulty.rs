@@ -0,0 +1,57 @@
+use async_graphql::*;
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+/// How strong the bot plays: Stockfish settings and the chances of deliberately weaker moves
+///
+/// Profiles "1" to "20" back the difficulty levels; other profiles are picked by name.
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct DifficultyProfile {
+    pub name: String,
+    /// Level the profile counts as (statistics, built-in engine strength), 1 to 20
+    pub level: i32,
+    /// Stockfish "Skill Level", -20 to 20
+    pub skill_level: i32,
+    /// Thinking time per move in milliseconds
+    pub movetime_ms: i32,
+    /// Strength limit given to Stockfish (None: unlimited)
+    pub uci_elo: Option<i32>,
+    /// Candidate moves Stockfish reports, among which weaker moves are picked
+    pub multipv: i32,
+    /// Chance of a random legal move played without searching
+    pub random_move_chance: f64,
+    /// Chance of a candidate that loses more than 2 pawns
+    pub blunder_chance: f64,
+    /// Chance of any candidate move
+    pub any_candidate_chance: f64,
+    /// Chance of a candidate among the weakest 70%
+    pub weak_candidate_chance: f64,
+    /// Chance of a candidate close to the best one (but not the best)
+    pub near_best_chance: f64,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Creates or replaces a difficulty profile
+#[derive(Debug, Clone, Serialize, Deserialize, InputObject)]
+pub struct DifficultyProfileInput {
+    pub name: String,
+    pub level: i32,
+    #[graphql(name = "skillLevel")]
+    pub skill_level: i32,
+    #[graphql(name = "movetimeMs")]
+    pub movetime_ms: i32,
+    #[graphql(name = "uciElo")]
+    pub uci_elo: Option<i32>,
+    #[graphql(default = 1)]
+    pub multipv: i32,
+    #[graphql(name = "randomMoveChance", default)]
+    pub random_move_chance: f64,
+    #[graphql(name = "blunderChance", default)]
+    pub blunder_chance: f64,
+    #[graphql(name = "anyCandidateChance", default)]
+    pub any_candidate_chance: f64,
+    #[graphql(name = "weakCandidateChance", default)]
+    pub weak_candidate_chance: f64,
+    #[graphql(name = "nearBestChance", default)]
+    pub near_best_chance: f64,
+}
@@ -2,13 +2,16 @@ use async_graphql::*;
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use chrono::{DateTime, Utc};
+use crate::models::AnalysisStatus;
 
 #[derive(Debug, Clone, FromRow, Serialize, Deserialize, SimpleObject)]
+#[graphql(complex)]
 pub struct Game {
     pub id: String,
     pub user_id: String,
     pub difficulty: i32,
     pub fen: String,
+    /// "active", "finished" or "aborted"
     pub status: String,
     pub result: Option<String>,
     pub created_at: DateTime<Utc>,
@@ -17,6 +20,126 @@ pub struct Game {
     pub end_time: Option<DateTime<Utc>>,
     pub duration_seconds: Option<i32>,
     pub moves_count: i32,
+    /// Rule that ended the game when it was drawn
+    pub draw_reason: Option<String>,
+    /// Position keys reached so far, newline separated (used for threefold repetition)
+    #[graphql(skip)]
+    pub position_history: String,
+    /// Side played by the human ("white" or "black")
+    pub player_color: String,
+    /// Starting position when the game didn't start from the standard position
+    pub start_fen: Option<String>,
+    /// Rules variant: "standard" or "chess960"
+    pub variant: String,
+    /// How the game ended (None while it is active)
+    pub termination: Option<Termination>,
+    /// Number of takebacks used; a game with takebacks cannot set a personal record
+    pub takebacks: i32,
+    /// Whether the player may take moves back in this game
+    pub takebacks_allowed: bool,
+    /// Number of engine hints used; a game with hints cannot set a personal record
+    pub hints_used: i32,
+    /// Engine playing against the human ("stockfish", "builtin" or a configured UCI engine)
+    pub engine: String,
+    /// Named difficulty profile the bot plays with (None: the profile of its level)
+    pub difficulty_profile: Option<String>,
+    /// Seed of the bot's random choices (see `engines::move_seed`)
+    pub rng_seed: i64,
+    /// Style of the bot's moves
+    pub personality: Personality,
+    /// Post-game analysis progress (None when no analysis was requested)
+    #[graphql(skip)]
+    pub analysis_status: Option<AnalysisStatus>,
+    /// Initial time per side in milliseconds (None for untimed games)
+    pub clock_base_ms: Option<i64>,
+    /// Time added to a side's clock after each of its moves
+    pub clock_increment_ms: i64,
+    /// Correspondence: every move gets `clock_base_ms` again instead of an increment
+    pub clock_per_move: bool,
+    /// White's remaining time when its clock last stopped
+    pub white_clock_ms: Option<i64>,
+    /// Black's remaining time when its clock last stopped
+    pub black_clock_ms: Option<i64>,
+    /// When the side to move started thinking
+    pub turn_started_at: Option<DateTime<Utc>>,
+}
+
+/// How a game ended
+#[derive(Enum, sqlx::Type, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum Termination {
+    Checkmate,
+    Stalemate,
+    InsufficientMaterial,
+    FiftyMoveRule,
+    ThreefoldRepetition,
+    Resignation,
+    Agreement,
+    Aborted,
+    Timeout,
+}
+
+/// Style of the bot's moves: which of the moves about as good as its choice it prefers
+/// (see `PersonalityService`)
+#[derive(Enum, sqlx::Type, Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[sqlx(type_name = "TEXT", rename_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum Personality {
+    /// Plays the engine's choice
+    #[default]
+    Balanced,
+    /// Captures, checks and attacks the king; keeps the queens on
+    Aggressive,
+    /// Develops, castles and takes the centre; avoids early adventures
+    Positional,
+    /// Trades pieces, castles and keeps its king covered
+    Defensive,
+    /// Leaves pieces that look free but can't be taken safely, goes for sharp lines
+    Trappy,
+}
+
+impl Personality {
+    /// Name stored in `games.personality` (e.g., "aggressive")
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Personality::Balanced => "balanced",
+            Personality::Aggressive => "aggressive",
+            Personality::Positional => "positional",
+            Personality::Defensive => "defensive",
+            Personality::Trappy => "trappy",
+        }
+    }
+}
+
+impl Termination {
+    /// Maps a draw rule reported by `ChessService::check_game_over` (None for checkmate)
+    pub fn from_draw_reason(draw_reason: Option<&str>) -> Termination {
+        match draw_reason {
+            Some("stalemate") => Termination::Stalemate,
+            Some("insufficient_material") => Termination::InsufficientMaterial,
+            Some("fifty_move_rule") => Termination::FiftyMoveRule,
+            Some("threefold_repetition") => Termination::ThreefoldRepetition,
+            // Any other reason is a draw the players agreed on
+            Some(_) => Termination::Agreement,
+            None => Termination::Checkmate,
+        }
+    }
+
+    /// Name stored in `games.termination` and `games.draw_reason` (e.g., "fifty_move_rule")
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Termination::Checkmate => "checkmate",
+            Termination::Stalemate => "stalemate",
+            Termination::InsufficientMaterial => "insufficient_material",
+            Termination::FiftyMoveRule => "fifty_move_rule",
+            Termination::ThreefoldRepetition => "threefold_repetition",
+            Termination::Resignation => "resignation",
+            Termination::Agreement => "agreement",
+            Termination::Aborted => "aborted",
+            Termination::Timeout => "timeout",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
@@ -25,11 +148,61 @@ pub struct GameMoveResult {
     pub stockfish_move: String,
     pub game_over: bool,
     pub winner: Option<String>,
+    /// Rule that ended the game when winner is "draw"
+    pub draw_reason: Option<String>,
     // Nouvelles infos timer
     pub move_time_ms: Option<i64>,
     pub total_time_seconds: Option<i32>,
     // Nouveau: derniers coups pour animation
     pub last_move: Option<LastMove>,
+    /// Full description of the move the player just made
+    pub player_move: MoveDetails,
+    /// Full description of the engine's reply (None when the game ended on the player's move)
+    pub engine_move: Option<MoveDetails>,
+}
+
+/// Everything needed to display a move without re-implementing chess rules
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct MoveDetails {
+    pub from: String,
+    pub to: String,
+    /// Move in coordinate notation (e.g., "e7e8q")
+    pub uci: String,
+    /// Move in Standard Algebraic Notation (e.g., "exd8=Q+")
+    pub san: String,
+    /// Moving piece ("pawn", "knight", "bishop", "rook", "queen", "king")
+    pub piece: String,
+    /// Side that played the move ("white" or "black")
+    pub color: String,
+    /// Piece taken by the move, if any
+    pub captured: Option<String>,
+    pub is_check: bool,
+    pub is_checkmate: bool,
+    /// "kingside" or "queenside" for castling moves
+    pub castling: Option<String>,
+    pub is_en_passant: bool,
+    /// Piece a pawn promotes to, if any
+    pub promotion: Option<String>,
+}
+
+/// A single ply of a game's move history
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, SimpleObject)]
+pub struct GameMove {
+    pub id: String,
+    pub game_id: String,
+    /// Half-move number, starting at 1
+    pub ply: i32,
+    /// Side that played the move ("white" or "black")
+    pub side: String,
+    /// Move in coordinate notation (e.g., "e2e4", "e7e8q")
+    pub uci: String,
+    /// Move in Standard Algebraic Notation (e.g., "e4", "Nxf7+")
+    pub san: String,
+    /// Position after the move in FEN notation
+    pub fen_after: String,
+    /// Time spent choosing the move (milliseconds)
+    pub think_time_ms: Option<i32>,
+    pub created_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
@@ -46,12 +219,92 @@ pub struct NewGameInput {
     #[graphql(name = "userId")]
     pub user_id: String,
     pub difficulty: i32,
+    /// Side to play: "white" (default), "black" or "random"
+    pub color: Option<String>,
+    /// Custom starting position in FEN notation (standard position when omitted)
+    #[graphql(name = "startFen")]
+    pub start_fen: Option<String>,
+    /// Rules variant: "standard" (default) or "chess960"
+    pub variant: Option<String>,
+    /// Chess960 starting position number, 0 to 959 (random when omitted)
+    #[graphql(name = "chess960Position")]
+    pub chess960_position: Option<i32>,
+    /// Allow the player to take moves back (default true)
+    #[graphql(name = "allowTakebacks", default = true)]
+    pub allow_takebacks: bool,
+    /// Clock settings (untimed game when omitted)
+    #[graphql(name = "timeControl")]
+    pub time_control: Option<TimeControlInput>,
+    /// Bot to play against (see the `bots` query); the level's engine when omitted
+    pub bot: Option<String>,
+    /// Named difficulty profile (see the `difficultyProfiles` query); its level replaces `difficulty`
+    #[graphql(name = "difficultyProfile")]
+    pub difficulty_profile: Option<String>,
+    /// Seed of the bot's random choices, to replay them (random when omitted)
+    pub seed: Option<i64>,
+    /// Style of the bot's moves (balanced when omitted)
+    pub personality: Option<Personality>,
+}
+
+/// Time control of a new game: base time + increment (e.g., 5+3) or correspondence
+#[derive(Debug, Clone, Serialize, Deserialize, InputObject)]
+pub struct TimeControlInput {
+    /// Initial time per side in seconds (e.g., 300 for 5+3)
+    #[graphql(name = "baseSeconds")]
+    pub base_seconds: Option<i32>,
+    /// Seconds added to a side's clock after each of its moves
+    #[graphql(name = "incrementSeconds", default = 0)]
+    pub increment_seconds: i32,
+    /// Correspondence: days allowed for each move (instead of baseSeconds)
+    #[graphql(name = "daysPerMove")]
+    pub days_per_move: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, InputObject)]
+pub struct ImportPgnInput {
+    #[graphql(name = "userId")]
+    pub user_id: String,
+    /// PGN text, one or more games
+    pub pgn: String,
+    /// Stockfish level used when continuing unfinished games
+    pub difficulty: i32,
+    /// Side the player had in the imported games: "white" (default) or "black"
+    pub color: Option<String>,
+    /// Count finished games in the player's statistics (never in personal records)
+    #[graphql(name = "countInStats", default = false)]
+    pub count_in_stats: bool,
+}
+
+/// Outcome of a draw offer made to the engine
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct DrawOfferResult {
+    pub accepted: bool,
+    /// Engine evaluation of the position from its own side, in centipawns
+    pub engine_evaluation: i32,
+    pub game: Game,
+}
+
+/// Best move suggested by a full-strength engine search
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct Hint {
+    pub best_move: MoveDetails,
+    /// Evaluation in centipawns from White's point of view (None when a mate was found)
+    pub evaluation_cp: Option<i32>,
+    /// Moves until mate from White's point of view (negative when Black mates)
+    pub mate_in: Option<i32>,
+    /// Principal variation in SAN, starting with the best move
+    pub pv: Vec<String>,
+    /// Search depth reached
+    pub depth: i32,
+    /// Hints used in the game so far, this one included
+    pub hints_used: i32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, InputObject)]
 pub struct MakeMoveInput {
     #[graphql(name = "gameId")]
     pub game_id: String,
+    /// Move in coordinate notation ("e2e4", "e7e8q") or SAN ("Nf3", "O-O", "e8=Q+")
     #[graphql(name = "playerMove")]
     pub player_move: String,
 }
\ No newline at end of file
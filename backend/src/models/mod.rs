@@ -1,5 +1,11 @@
 pub mod user;
 pub mod game;
+pub mod position;
+pub mod analysis;
+pub mod difficulty;
 
-pub use user::{User, UserRecord, UserLevelStats, UserProfile};
-pub use game::{Game, NewGameInput, MakeMoveInput, GameMoveResult};
\ No newline at end of file
+pub use user::{User, UserRecord, UserLevelStats, UserModeStats, UserProfile};
+pub use game::{Game, GameMove, MoveDetails, Termination, Personality, DrawOfferResult, Hint, NewGameInput, TimeControlInput, ImportPgnInput, MakeMoveInput, GameMoveResult};
+pub use analysis::{AnalysisStatus, MoveClassification, MoveAnalysis, GameAnalysis};
+pub use difficulty::{DifficultyProfile, DifficultyProfileInput};
+pub use position::{LegalMoves, SquareMoves, Evaluation, AnalysisInput, PositionAnalysis, AnalysisLine};
\ No newline at end of file
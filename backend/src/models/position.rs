@@ -0,0 +1,90 @@
+use async_graphql::*;
+use serde::{Deserialize, Serialize};
+use crate::models::MoveDetails;
+
+/// Every legal move of a position, grouped by origin square
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct LegalMoves {
+    pub fen: String,
+    /// Side to move ("white" or "black")
+    pub side_to_move: String,
+    /// Whether the side to move is in check
+    pub is_check: bool,
+    /// Square of the side to move's king (e.g., "e1")
+    pub king_square: String,
+    /// Squares holding a piece that can move, sorted by square name
+    pub squares: Vec<SquareMoves>,
+}
+
+/// Legal moves of the piece standing on one square
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct SquareMoves {
+    /// Origin square (e.g., "g1")
+    pub from: String,
+    /// Piece on the square ("pawn", "knight", "bishop", "rook", "queen", "king")
+    pub piece: String,
+    /// Destination squares, without duplicates (castling lists the king's landing square)
+    pub targets: Vec<String>,
+    /// Every move from the square; a promotion appears once per promotion piece
+    pub moves: Vec<MoveDetails>,
+}
+
+/// Engine evaluation of a position, from White's point of view
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct Evaluation {
+    pub fen: String,
+    /// Advantage in centipawns (None when a mate was found)
+    pub evaluation_cp: Option<i32>,
+    /// Moves until mate (negative when Black mates)
+    pub mate_in: Option<i32>,
+    /// Best move for the side to move (None when the game is over)
+    pub best_move: Option<MoveDetails>,
+    /// Principal variation in SAN, starting with the best move
+    pub pv: Vec<String>,
+    /// Search depth reached
+    pub depth: i32,
+}
+
+/// Position to analyse and engine limits of a Multi-PV analysis
+#[derive(Debug, Clone, Serialize, Deserialize, InputObject)]
+pub struct AnalysisInput {
+    /// Position in FEN notation (or gameId + ply)
+    pub fen: Option<String>,
+    #[graphql(name = "gameId")]
+    pub game_id: Option<String>,
+    /// Position after this many plies of the game (0 = starting position, latest when omitted)
+    pub ply: Option<i32>,
+    /// Number of lines to return, 1 to 10 (default 3)
+    #[graphql(default = 3)]
+    pub lines: i32,
+    /// Maximum search depth
+    pub depth: Option<i32>,
+    /// Maximum search time in milliseconds
+    #[graphql(name = "movetimeMs")]
+    pub movetime_ms: Option<i32>,
+}
+
+/// Top engine lines of a position
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct PositionAnalysis {
+    pub fen: String,
+    /// Lines sorted from best to worst for the side to move
+    pub lines: Vec<AnalysisLine>,
+}
+
+/// One engine line, scored from White's point of view
+#[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
+pub struct AnalysisLine {
+    /// 1 for the best line
+    pub rank: i32,
+    /// Advantage in centipawns (None when a mate was found)
+    pub evaluation_cp: Option<i32>,
+    /// Moves until mate (negative when Black mates)
+    pub mate_in: Option<i32>,
+    /// Search depth reached for this line
+    pub depth: i32,
+    /// Moves in SAN
+    pub pv: Vec<String>,
+    /// Same moves in coordinate notation
+    pub pv_uci: Vec<String>,
+}
@@ -62,6 +62,25 @@ pub struct UserLevelStats {
     pub average_moves: i32,
 }
 
+/// Statistics for a user in a non-standard game mode at a specific difficulty level
+#[derive(Debug, Clone, FromRow, Serialize, Deserialize, SimpleObject)]
+pub struct UserModeStats {
+    pub id: String,
+    pub user_id: String,
+    /// Game mode ("custom_position")
+    pub mode: String,
+    /// Difficulty level (1-20, Stockfish depth)
+    pub difficulty: i32,
+    /// Total games played in this mode at this level
+    pub games_played: i32,
+    /// Total games won in this mode at this level
+    pub games_won: i32,
+    /// Total time spent (seconds)
+    pub total_time_seconds: i32,
+    /// Total moves made
+    pub total_moves: i32,
+}
+
 /// Complete user profile with records and statistics
 #[derive(Debug, Clone, Serialize, Deserialize, SimpleObject)]
 pub struct UserProfile {
@@ -71,4 +90,6 @@ pub struct UserProfile {
     pub records: Vec<UserRecord>,
     /// Detailed statistics for each difficulty level
     pub level_stats: Vec<UserLevelStats>,
+    /// Statistics for games outside the standard mode, tracked separately
+    pub mode_stats: Vec<UserModeStats>,
 }
\ No newline at end of file
@@ -0,0 +1,385 @@
+use crate::models::{
+    Game, Evaluation, AnalysisInput, PositionAnalysis, AnalysisLine, AnalysisStatus, MoveClassification,
+    MoveAnalysis, GameAnalysis,
+};
+use crate::services::ChessService;
+use crate::services::StockfishService;
+use crate::services::chess_service::STARTING_FEN;
+use crate::services::stockfish_service::{EngineLine, EngineScore};
+use crate::database::{
+    get_game_by_id, get_game_moves, get_game_analysis, replace_game_analysis, set_analysis_status,
+    get_next_pending_analysis,
+};
+use sqlx::SqlitePool;
+use chrono::Utc;
+use uuid::Uuid;
+
+/// Depth used when an evaluation doesn't ask for one
+const DEFAULT_EVALUATION_DEPTH: i32 = 16;
+
+/// Deepest search an evaluation may ask for
+const MAX_EVALUATION_DEPTH: i32 = 30;
+
+/// Longest search time an analysis may ask for (milliseconds)
+const MAX_ANALYSIS_MOVETIME_MS: i32 = 10_000;
+
+/// Most lines a Multi-PV analysis may ask for
+const MAX_ANALYSIS_LINES: i32 = 10;
+
+/// Search limits for each position of a post-game analysis
+const GAME_ANALYSIS_DEPTH: i32 = 14;
+const GAME_ANALYSIS_MOVETIME_MS: i32 = 1000;
+
+/// Evaluations are capped to this many centipawns when measuring the loss of a move,
+/// so that missing a mate in a won position is not worse than blundering a piece
+const LOSS_EVAL_CAP_CP: i32 = 1000;
+
+/// Service responsible for engine analysis of positions, outside of any game
+pub struct AnalysisService;
+
+impl AnalysisService {
+    /// Evaluates a position at full strength
+    /// 
+    /// # Arguments
+    /// * `fen` - Position to evaluate in FEN notation (validated first)
+    /// * `depth` - Search depth, 1 to MAX_EVALUATION_DEPTH (DEFAULT_EVALUATION_DEPTH when None)
+    /// 
+    /// # Returns
+    /// Result<Evaluation, String> - Score from White's point of view, best move and principal variation
+    pub async fn evaluate(fen: &str, depth: Option<i32>) -> Result<Evaluation, String> {
+        let depth = depth.unwrap_or(DEFAULT_EVALUATION_DEPTH);
+        if !(1..=MAX_EVALUATION_DEPTH).contains(&depth) {
+            return Err(format!("Depth must be between 1 and {}", MAX_EVALUATION_DEPTH));
+        }
+        let fen = ChessService::validate_fen(fen, ChessService::is_chess960_fen(fen))?;
+
+        let analysis = StockfishService::analyse(&fen, Some(depth), None, 1)
+            .await
+            .map_err(|e| format!("Stockfish error: {}", e))?;
+
+        let best_move = match &analysis.best_move {
+            Some(best_move) => Some(
+                ChessService::describe_move(&fen, best_move)
+                    .map_err(|e| format!("Stockfish returned an invalid move {}: {}", best_move, e))?,
+            ),
+            None => None,
+        };
+
+        let line = analysis.lines.first();
+        let (evaluation_cp, mate_in) = line.map_or((None, None), |l| Self::white_score(&fen, l.score));
+        let pv = match (line, &best_move) {
+            (Some(line), Some(best_move)) if line.pv.first() == Some(&best_move.uci) => ChessService::line_to_san(&fen, &line.pv),
+            (_, Some(best_move)) => vec![best_move.san.clone()],
+            _ => Vec::new(),
+        };
+
+        Ok(Evaluation {
+            evaluation_cp,
+            mate_in,
+            best_move,
+            pv,
+            depth: line.map_or(0, |l| l.depth),
+            fen,
+        })
+    }
+
+    /// Returns the top engine lines of a position, at full strength
+    /// 
+    /// The position is either a FEN or a stored game at a given ply.
+    /// 
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `input` - Position, number of lines and depth/time limits
+    /// 
+    /// # Returns
+    /// Result<PositionAnalysis, String> - Lines from best to worst, scored from White's point of view
+    pub async fn analyse_position(pool: &SqlitePool, input: AnalysisInput) -> Result<PositionAnalysis, String> {
+        if !(1..=MAX_ANALYSIS_LINES).contains(&input.lines) {
+            return Err(format!("Lines must be between 1 and {}", MAX_ANALYSIS_LINES));
+        }
+        if input.depth.is_some_and(|depth| !(1..=MAX_EVALUATION_DEPTH).contains(&depth)) {
+            return Err(format!("Depth must be between 1 and {}", MAX_EVALUATION_DEPTH));
+        }
+        if input.movetime_ms.is_some_and(|ms| !(1..=MAX_ANALYSIS_MOVETIME_MS).contains(&ms)) {
+            return Err(format!("Movetime must be between 1 and {} ms", MAX_ANALYSIS_MOVETIME_MS));
+        }
+        // Without limits, analyse as deep as a default evaluation
+        let depth = match (input.depth, input.movetime_ms) {
+            (None, None) => Some(DEFAULT_EVALUATION_DEPTH),
+            (depth, _) => depth,
+        };
+
+        let fen = match (&input.fen, &input.game_id) {
+            (Some(fen), None) => ChessService::validate_fen(fen, ChessService::is_chess960_fen(fen))?,
+            (None, Some(game_id)) => Self::game_position(pool, game_id, input.ply).await?,
+            _ => return Err("Provide either a FEN or a game ID".to_string()),
+        };
+
+        let analysis = StockfishService::analyse(&fen, depth, input.movetime_ms, input.lines as u32)
+            .await
+            .map_err(|e| format!("Stockfish error: {}", e))?;
+
+        let lines = analysis
+            .lines
+            .iter()
+            .take(input.lines as usize)
+            .map(|line| Self::analysis_line(&fen, line))
+            .collect();
+        Ok(PositionAnalysis { fen, lines })
+    }
+
+    /// Position of a stored game after the given number of plies (latest position when None)
+    async fn game_position(pool: &SqlitePool, game_id: &str, ply: Option<i32>) -> Result<String, String> {
+        let game = get_game_by_id(pool, game_id)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?
+            .ok_or_else(|| "Game not found".to_string())?;
+
+        let ply = match ply {
+            None => return Ok(game.fen),
+            Some(ply) => ply,
+        };
+        if ply == 0 {
+            return Ok(game.start_fen.unwrap_or_else(|| STARTING_FEN.to_string()));
+        }
+
+        let moves = get_game_moves(pool, game_id)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?;
+        moves
+            .into_iter()
+            .find(|m| m.ply == ply)
+            .map(|m| m.fen_after)
+            .ok_or_else(|| format!("Ply {} not found in game (0 to {})", ply, game.moves_count))
+    }
+
+    /// Converts an engine line to SAN, scored from White's point of view
+    fn analysis_line(fen: &str, line: &EngineLine) -> AnalysisLine {
+        let (evaluation_cp, mate_in) = Self::white_score(fen, line.score);
+        let pv = ChessService::line_to_san(fen, &line.pv);
+        AnalysisLine {
+            rank: line.multipv as i32,
+            evaluation_cp,
+            mate_in,
+            depth: line.depth,
+            // Keep both notations the same length if the engine sent an illegal move
+            pv_uci: line.pv.iter().take(pv.len()).cloned().collect(),
+            pv,
+        }
+    }
+
+    /// Splits a score for the side to move into (centipawns, mate in N) from White's point of view
+    fn white_score(fen: &str, score: EngineScore) -> (Option<i32>, Option<i32>) {
+        match score.for_white(fen) {
+            EngineScore::Cp(cp) => (Some(cp), None),
+            EngineScore::Mate(moves) => (None, Some(moves)),
+        }
+    }
+
+    /// Queues a finished game for post-game analysis (again, if it was already analysed)
+    /// 
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `game_id` - Unique identifier of the game
+    /// 
+    /// # Returns
+    /// The game with its analysis pending
+    pub async fn request_game_analysis(pool: &SqlitePool, game_id: &str) -> Result<Game, String> {
+        let mut game = get_game_by_id(pool, game_id)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?
+            .ok_or_else(|| "Game not found".to_string())?;
+
+        if game.status != "finished" {
+            return Err("Only finished games can be analysed".to_string());
+        }
+        if game.analysis_status == Some(AnalysisStatus::Running) {
+            return Err("The game is already being analysed".to_string());
+        }
+
+        set_analysis_status(pool, &game.id, AnalysisStatus::Pending)
+            .await
+            .map_err(|e| format!("Database update error: {}", e))?;
+        game.analysis_status = Some(AnalysisStatus::Pending);
+        Ok(game)
+    }
+
+    /// Analyses every finished game waiting in the queue, oldest first
+    /// 
+    /// Run periodically in the background; a game whose analysis fails is marked as failed
+    /// and not retried until it is requested again.
+    /// 
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// 
+    /// # Returns
+    /// Number of games analysed
+    pub async fn run_pending_analyses(pool: &SqlitePool) -> Result<usize, String> {
+        let mut analysed = 0;
+        while let Some(game) = get_next_pending_analysis(pool)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?
+        {
+            set_analysis_status(pool, &game.id, AnalysisStatus::Running)
+                .await
+                .map_err(|e| format!("Database update error: {}", e))?;
+
+            let status = match Self::analyse_game(pool, &game).await {
+                Ok(()) => {
+                    analysed += 1;
+                    AnalysisStatus::Done
+                }
+                Err(e) => {
+                    println!("⚠️ Analysis of game {} failed: {}", game.id, e);
+                    AnalysisStatus::Failed
+                }
+            };
+            set_analysis_status(pool, &game.id, status)
+                .await
+                .map_err(|e| format!("Database update error: {}", e))?;
+        }
+        Ok(analysed)
+    }
+
+    /// Evaluates every position of a game and stores the verdict on each ply
+    async fn analyse_game(pool: &SqlitePool, game: &Game) -> Result<(), String> {
+        let moves = get_game_moves(pool, &game.id)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?;
+
+        let mut positions = vec![game.start_fen.clone().unwrap_or_else(|| STARTING_FEN.to_string())];
+        positions.extend(moves.iter().map(|m| m.fen_after.clone()));
+
+        // (score from White's point of view, best move) of every position
+        let mut evaluations = Vec::with_capacity(positions.len());
+        for fen in &positions {
+            evaluations.push(Self::evaluate_game_position(fen).await?);
+        }
+
+        let mut analysis = Vec::with_capacity(moves.len());
+        for (index, game_move) in moves.iter().enumerate() {
+            let (before, best_move) = &evaluations[index];
+            let (after, _) = &evaluations[index + 1];
+
+            let capped = |score: EngineScore| score.as_cp().clamp(-LOSS_EVAL_CAP_CP, LOSS_EVAL_CAP_CP);
+            let sign = if game_move.side == "white" { 1 } else { -1 };
+            let is_best = best_move.as_deref() == Some(game_move.uci.as_str());
+            let cp_loss = if is_best { 0 } else { (sign * (capped(*before) - capped(*after))).max(0) };
+
+            analysis.push(MoveAnalysis {
+                id: Uuid::new_v4().to_string(),
+                game_id: game.id.clone(),
+                ply: game_move.ply,
+                side: game_move.side.clone(),
+                uci: game_move.uci.clone(),
+                san: game_move.san.clone(),
+                eval_before_cp: before.as_cp(),
+                eval_after_cp: after.as_cp(),
+                mate_in: match after {
+                    EngineScore::Mate(moves) => Some(*moves),
+                    EngineScore::Cp(_) => None,
+                },
+                best_move_san: best_move
+                    .as_ref()
+                    .and_then(|m| ChessService::describe_move(&positions[index], m).ok())
+                    .map(|details| details.san),
+                best_move: best_move.clone(),
+                cp_loss,
+                classification: Self::classify(is_best, cp_loss),
+                created_at: Utc::now(),
+            });
+        }
+
+        replace_game_analysis(pool, &game.id, &analysis)
+            .await
+            .map_err(|e| format!("Database update error: {}", e))?;
+
+        println!("🔬 Analysed game {} ({} plies)", game.id, analysis.len());
+        Ok(())
+    }
+
+    /// Score (from White's point of view) and best move of one position of a game
+    async fn evaluate_game_position(fen: &str) -> Result<(EngineScore, Option<String>), String> {
+        // Final positions are scored by the rules: the engine has no move to search
+        match ChessService::check_game_over(fen, &[]) {
+            (true, Some(winner), None) => {
+                let mate = EngineScore::Mate(1).as_cp() + 1;
+                return Ok((EngineScore::Cp(if winner == "white" { mate } else { -mate }), None));
+            }
+            (true, _, _) => return Ok((EngineScore::Cp(0), None)),
+            _ => {}
+        }
+
+        let analysis = StockfishService::analyse(fen, Some(GAME_ANALYSIS_DEPTH), Some(GAME_ANALYSIS_MOVETIME_MS), 1)
+            .await
+            .map_err(|e| format!("Stockfish error: {}", e))?;
+        let score = analysis.lines.first().map_or(EngineScore::Cp(0), |line| line.score.for_white(fen));
+        Ok((score, analysis.best_move))
+    }
+
+    /// Classifies a move by the centipawns it lost
+    fn classify(is_best: bool, cp_loss: i32) -> MoveClassification {
+        match cp_loss {
+            _ if is_best => MoveClassification::Best,
+            0..=49 => MoveClassification::Good,
+            50..=99 => MoveClassification::Inaccuracy,
+            100..=299 => MoveClassification::Mistake,
+            _ => MoveClassification::Blunder,
+        }
+    }
+
+    /// Post-game analysis of a game, with per-side accuracy once it is done
+    /// 
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `game` - Analysed game
+    /// 
+    /// # Returns
+    /// None when no analysis was requested for the game
+    pub async fn get_game_analysis(pool: &SqlitePool, game: &Game) -> Result<Option<GameAnalysis>, String> {
+        let status = match game.analysis_status {
+            Some(status) => status,
+            None => return Ok(None),
+        };
+        if status != AnalysisStatus::Done {
+            return Ok(Some(GameAnalysis { status, white_accuracy: None, black_accuracy: None, moves: Vec::new() }));
+        }
+
+        let moves = get_game_analysis(pool, &game.id)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?;
+        Ok(Some(GameAnalysis {
+            status,
+            white_accuracy: Self::accuracy(&moves, "white"),
+            black_accuracy: Self::accuracy(&moves, "black"),
+            moves,
+        }))
+    }
+
+    /// Average accuracy of a side's moves, 0 to 100 (None when the side played no move)
+    /// 
+    /// Each move scores from the winning chances it gave away, so a 100 centipawn slip
+    /// costs more in a balanced position than in an already decided one.
+    fn accuracy(moves: &[MoveAnalysis], side: &str) -> Option<f64> {
+        let accuracies: Vec<f64> = moves
+            .iter()
+            .filter(|m| m.side == side)
+            .map(|m| {
+                let sign = if side == "white" { 1 } else { -1 };
+                let lost = Self::win_percent(sign * m.eval_before_cp) - Self::win_percent(sign * m.eval_after_cp);
+                (103.1668 * (-0.04354 * lost.max(0.0)).exp() - 3.1669).clamp(0.0, 100.0)
+            })
+            .collect();
+        if accuracies.is_empty() {
+            return None;
+        }
+        let average = accuracies.iter().sum::<f64>() / accuracies.len() as f64;
+        Some((average * 10.0).round() / 10.0)
+    }
+
+    /// Winning chances (0 to 100) of a side from its evaluation in centipawns
+    fn win_percent(cp: i32) -> f64 {
+        let cp = cp.clamp(-LOSS_EVAL_CAP_CP, LOSS_EVAL_CAP_CP) as f64;
+        50.0 + 50.0 * (2.0 / (1.0 + (-0.00368208 * cp).exp()) - 1.0)
+    }
+}
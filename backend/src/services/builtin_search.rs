@@ -0,0 +1,518 @@
+use chess::{BitBoard, Board, BoardStatus, ChessMove, Color, MoveGen, Piece, ALL_PIECES};
+use rand::seq::SliceRandom;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::time::{Duration, Instant};
+use tracing::debug;
+use crate::models::Personality;
+use crate::services::ChessService;
+use crate::services::personality_service::{PersonalityService, PERSONALITY_MARGIN_CP};
+use crate::services::stockfish_service::{EngineAnalysis, EngineLine, EngineScore};
+
+/// Score of a mate on the board; mates found closer to the root score higher
+const MATE_SCORE: i32 = 30_000;
+
+/// Scores above this are mates
+const MATE_THRESHOLD: i32 = MATE_SCORE - 1_000;
+
+/// Bound larger than any score
+const INFINITY: i32 = 32_000;
+
+/// Number of transposition table entries (a power of two)
+const TT_SIZE: usize = 1 << 18;
+
+/// Nodes searched between two looks at the clock
+const TIME_CHECK_NODES: u64 = 1024;
+
+/// Material values in centipawns, indexed by `Piece::to_index`
+pub(crate) const PIECE_VALUES: [i32; 6] = [100, 320, 330, 500, 900, 0];
+
+// Piece-square tables from White's point of view, rank 8 first (simplified evaluation function)
+#[rustfmt::skip]
+const PAWN_TABLE: [i32; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+    50, 50, 50, 50, 50, 50, 50, 50,
+    10, 10, 20, 30, 30, 20, 10, 10,
+     5,  5, 10, 25, 25, 10,  5,  5,
+     0,  0,  0, 20, 20,  0,  0,  0,
+     5, -5,-10,  0,  0,-10, -5,  5,
+     5, 10, 10,-20,-20, 10, 10,  5,
+     0,  0,  0,  0,  0,  0,  0,  0,
+];
+
+#[rustfmt::skip]
+const KNIGHT_TABLE: [i32; 64] = [
+    -50,-40,-30,-30,-30,-30,-40,-50,
+    -40,-20,  0,  0,  0,  0,-20,-40,
+    -30,  0, 10, 15, 15, 10,  0,-30,
+    -30,  5, 15, 20, 20, 15,  5,-30,
+    -30,  0, 15, 20, 20, 15,  0,-30,
+    -30,  5, 10, 15, 15, 10,  5,-30,
+    -40,-20,  0,  5,  5,  0,-20,-40,
+    -50,-40,-30,-30,-30,-30,-40,-50,
+];
+
+#[rustfmt::skip]
+const BISHOP_TABLE: [i32; 64] = [
+    -20,-10,-10,-10,-10,-10,-10,-20,
+    -10,  0,  0,  0,  0,  0,  0,-10,
+    -10,  0,  5, 10, 10,  5,  0,-10,
+    -10,  5,  5, 10, 10,  5,  5,-10,
+    -10,  0, 10, 10, 10, 10,  0,-10,
+    -10, 10, 10, 10, 10, 10, 10,-10,
+    -10,  5,  0,  0,  0,  0,  5,-10,
+    -20,-10,-10,-10,-10,-10,-10,-20,
+];
+
+#[rustfmt::skip]
+const ROOK_TABLE: [i32; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+     5, 10, 10, 10, 10, 10, 10,  5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+     0,  0,  0,  5,  5,  0,  0,  0,
+];
+
+#[rustfmt::skip]
+const QUEEN_TABLE: [i32; 64] = [
+    -20,-10,-10, -5, -5,-10,-10,-20,
+    -10,  0,  0,  0,  0,  0,  0,-10,
+    -10,  0,  5,  5,  5,  5,  0,-10,
+     -5,  0,  5,  5,  5,  5,  0, -5,
+      0,  0,  5,  5,  5,  5,  0, -5,
+    -10,  5,  5,  5,  5,  5,  0,-10,
+    -10,  0,  5,  0,  0,  0,  0,-10,
+    -20,-10,-10, -5, -5,-10,-10,-20,
+];
+
+#[rustfmt::skip]
+const KING_MIDDLEGAME_TABLE: [i32; 64] = [
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -20,-30,-30,-40,-40,-30,-30,-20,
+    -10,-20,-20,-20,-20,-20,-20,-10,
+     20, 20,  0,  0,  0,  0, 20, 20,
+     20, 30, 10,  0,  0, 10, 30, 20,
+];
+
+#[rustfmt::skip]
+const KING_ENDGAME_TABLE: [i32; 64] = [
+    -50,-40,-30,-20,-20,-30,-40,-50,
+    -30,-20,-10,  0,  0,-10,-20,-30,
+    -30,-10, 20, 30, 30, 20,-10,-30,
+    -30,-10, 30, 40, 40, 30,-10,-30,
+    -30,-10, 30, 40, 40, 30,-10,-30,
+    -30,-10, 20, 30, 30, 20,-10,-30,
+    -30,-30,  0,  0,  0,  0,-30,-30,
+    -50,-30,-30,-30,-30,-30,-30,-50,
+];
+
+/// How hard the built-in engine thinks
+#[derive(Debug, Clone, Copy)]
+pub struct SearchLimits {
+    /// Deepest iteration, in plies
+    pub depth: u8,
+    /// Time after which the search keeps the last finished iteration
+    pub movetime_ms: u64,
+    /// Moves scoring within this many centipawns of the best one may be played instead
+    pub margin_cp: i32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Bound {
+    Exact,
+    /// The score is at least this much (beta cutoff)
+    Lower,
+    /// The score is at most this much (no move raised alpha)
+    Upper,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct TtEntry {
+    hash: u64,
+    depth: u8,
+    score: i32,
+    bound: Bound,
+    best_move: Option<ChessMove>,
+}
+
+/// Alpha-beta search of the built-in engine (runs on the calling thread)
+///
+/// Iterative deepening with a transposition table, quiescence search on captures and a
+/// material + piece-square evaluation. Chess960 castling is only considered for the move
+/// being chosen, as the chess crate doesn't generate it deeper in the tree.
+pub struct BuiltinSearch {
+    tt: Vec<Option<TtEntry>>,
+    nodes: u64,
+    deadline: Instant,
+    /// Set once depth 1 is done: a move is always available before the search can stop
+    can_stop: bool,
+    stopped: bool,
+    /// Positions from the root to the current node, for repetitions
+    path: Vec<u64>,
+}
+
+impl BuiltinSearch {
+    /// Search limits of a difficulty level
+    ///
+    /// Low levels search shallow and pick among several reasonable moves; from level 10 the
+    /// engine always plays its best move, mostly limited by time.
+    pub fn limits_for_level(difficulty: i32) -> SearchLimits {
+        let difficulty = difficulty.clamp(1, 20);
+        let depth = match difficulty {
+            1..=2 => 1,
+            3..=4 => 2,
+            5..=6 => 3,
+            7..=9 => 4,
+            10..=12 => 5,
+            13..=16 => 6,
+            _ => 8,
+        };
+        SearchLimits {
+            depth,
+            movetime_ms: 100 + 50 * difficulty as u64,
+            margin_cp: (10 - difficulty).max(0) * 30,
+        }
+    }
+
+    /// Chooses a move for the side to move
+    ///
+    /// The seed only makes the choice among the candidates reproducible. The candidates come
+    /// from the last iteration finished within `movetime_ms`, so on a slower or busier machine
+    /// the same seed can still give another move.
+    ///
+    /// # Arguments
+    /// * `fen` - Current position (Shredder-FEN castling for Chess960)
+    /// * `limits` - Depth, time and randomness of the search
+    /// * `personality` - Style preferring some moves close to the chosen one
+    /// * `seed` - Seed of the choice among moves within the margin
+    ///
+    /// # Returns
+    /// Result<String, String> - Move in coordinate notation or error message
+    pub fn best_move(fen: &str, limits: &SearchLimits, personality: Personality, seed: u64) -> Result<String, String> {
+        // A personality may prefer moves a little worse than the chosen one: score them exactly too
+        let margin = match personality {
+            Personality::Balanced => limits.margin_cp,
+            _ => limits.margin_cp + PERSONALITY_MARGIN_CP,
+        };
+        let (scores, _) = Self::search(fen, limits, margin)?;
+
+        let best_score = scores[0].1;
+        let candidates: Vec<ChessMove> = scores
+            .iter()
+            .filter(|(_, score)| *score >= best_score - limits.margin_cp)
+            .map(|(chess_move, _)| *chess_move)
+            .collect();
+        let chosen = candidates
+            .choose(&mut StdRng::seed_from_u64(seed))
+            .copied()
+            .unwrap_or(scores[0].0);
+        if personality == Personality::Balanced || best_score.abs() > MATE_THRESHOLD {
+            return Ok(chosen.to_string());
+        }
+
+        let lines: Vec<EngineLine> = scores
+            .iter()
+            .enumerate()
+            .map(|(index, (chess_move, score))| EngineLine {
+                multipv: index as u32 + 1,
+                depth: 0,
+                score: EngineScore::Cp(*score),
+                pv: vec![chess_move.to_string()],
+            })
+            .collect();
+        Ok(PersonalityService::pick_move(fen, &lines, &chosen.to_string(), personality))
+    }
+
+    /// Finds the best move and its score, for when Stockfish is not available
+    ///
+    /// # Arguments
+    /// * `fen` - Position to analyse
+    /// * `limits` - Depth and time of the search (the margin is ignored)
+    ///
+    /// # Returns
+    /// Result<EngineAnalysis, String> - Best move with a one-move line, score for the side to move
+    pub fn analyse(fen: &str, limits: &SearchLimits) -> Result<EngineAnalysis, String> {
+        let (scores, depth) = Self::search(fen, limits, 0)?;
+        let (best_move, score) = scores[0];
+        let score = if score > MATE_THRESHOLD {
+            EngineScore::Mate((MATE_SCORE - score + 1) / 2)
+        } else if score < -MATE_THRESHOLD {
+            EngineScore::Mate(-(MATE_SCORE + score) / 2)
+        } else {
+            EngineScore::Cp(score)
+        };
+        Ok(EngineAnalysis {
+            best_move: Some(best_move.to_string()),
+            lines: vec![EngineLine { multipv: 1, depth: i32::from(depth), score, pv: vec![best_move.to_string()] }],
+        })
+    }
+
+    /// Iterative deepening over the legal moves of a position
+    ///
+    /// # Returns
+    /// Result<(Vec<(ChessMove, i32)>, u8), String> - Root moves best first (exact scores within
+    /// `margin` of the best) and the depth of the last finished iteration
+    fn search(fen: &str, limits: &SearchLimits, margin: i32) -> Result<(Vec<(ChessMove, i32)>, u8), String> {
+        let board = ChessService::load_board(fen)?;
+        let mut root_moves: Vec<(ChessMove, Board)> = Vec::new();
+        for chess_move in ChessService::legal_moves(&board, fen) {
+            root_moves.push((chess_move, ChessService::play(&board, chess_move)?));
+        }
+        if root_moves.is_empty() {
+            return Err("No legal moves found".to_string());
+        }
+
+        let mut search = BuiltinSearch {
+            tt: vec![None; TT_SIZE],
+            nodes: 0,
+            deadline: Instant::now() + Duration::from_millis(limits.movetime_ms),
+            can_stop: false,
+            stopped: false,
+            path: vec![board.get_hash()],
+        };
+
+        let mut scores: Vec<(ChessMove, i32)> = Vec::new();
+        let mut finished_depth = 0;
+        for depth in 1..=limits.depth.max(1) {
+            // Best move of the previous iteration first: it makes the others fail low quickly
+            if let Some((best, _)) = scores.first() {
+                let best = *best;
+                root_moves.sort_by_key(|(chess_move, _)| *chess_move != best);
+            }
+            let Some(iteration) = search.search_root(&root_moves, depth, margin) else {
+                break;
+            };
+            scores = iteration;
+            finished_depth = depth;
+            search.can_stop = true;
+            debug!(
+                "Built-in search: depth={} best={} score={} nodes={}",
+                depth, scores[0].0, scores[0].1, search.nodes
+            );
+            if scores[0].1.abs() > MATE_THRESHOLD || Instant::now() >= search.deadline {
+                break;
+            }
+        }
+        Ok((scores, finished_depth))
+    }
+
+    /// Searches every root move, best first
+    ///
+    /// Moves are searched against the best score so far minus the margin, so every move
+    /// close enough to the best gets an exact score. Returns None when time ran out.
+    fn search_root(&mut self, root_moves: &[(ChessMove, Board)], depth: u8, margin: i32) -> Option<Vec<(ChessMove, i32)>> {
+        let mut best = -INFINITY;
+        let mut scores: Vec<(ChessMove, i32)> = Vec::new();
+        for (chess_move, child) in root_moves {
+            let alpha = if best == -INFINITY { -INFINITY } else { best - margin - 1 };
+            let score = -self.negamax(child, depth - 1, 1, -INFINITY, -alpha);
+            if self.stopped {
+                return None;
+            }
+            best = best.max(score);
+            scores.push((*chess_move, score));
+        }
+        scores.sort_by_key(|(_, score)| -score);
+        Some(scores)
+    }
+
+    fn negamax(&mut self, board: &Board, depth: u8, ply: i32, mut alpha: i32, beta: i32) -> i32 {
+        if self.out_of_time() {
+            return 0;
+        }
+        let hash = board.get_hash();
+        if self.path.contains(&hash) {
+            return 0;
+        }
+        match board.status() {
+            BoardStatus::Checkmate => return -MATE_SCORE + ply,
+            BoardStatus::Stalemate => return 0,
+            BoardStatus::Ongoing => {}
+        }
+
+        let in_check = board.checkers().popcnt() > 0;
+        if depth == 0 && !in_check {
+            return self.quiesce(board, alpha, beta);
+        }
+        // A check is always answered, even at the end of the search
+        let depth = depth.max(1);
+
+        let slot = (hash as usize) & (TT_SIZE - 1);
+        let mut tt_move = None;
+        if let Some(entry) = self.tt[slot].filter(|entry| entry.hash == hash) {
+            tt_move = entry.best_move;
+            if entry.depth >= depth {
+                let score = Self::score_from_tt(entry.score, ply);
+                match entry.bound {
+                    Bound::Exact => return score,
+                    Bound::Lower if score >= beta => return score,
+                    Bound::Upper if score <= alpha => return score,
+                    _ => {}
+                }
+            }
+        }
+
+        let mut moves: Vec<ChessMove> = MoveGen::new_legal(board).collect();
+        Self::order_moves(board, &mut moves, tt_move);
+
+        let original_alpha = alpha;
+        let mut best_score = -INFINITY;
+        let mut best_move = None;
+        self.path.push(hash);
+        for chess_move in moves {
+            let child = board.make_move_new(chess_move);
+            let score = -self.negamax(&child, depth - 1, ply + 1, -beta, -alpha);
+            if self.stopped {
+                self.path.pop();
+                return 0;
+            }
+            if score > best_score {
+                best_score = score;
+                best_move = Some(chess_move);
+            }
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+        self.path.pop();
+
+        let bound = if best_score <= original_alpha {
+            Bound::Upper
+        } else if best_score >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        self.tt[slot] = Some(TtEntry {
+            hash,
+            depth,
+            score: Self::score_to_tt(best_score, ply),
+            bound,
+            best_move,
+        });
+        best_score
+    }
+
+    /// Searches captures until the position is quiet, so exchanges are never cut in half
+    fn quiesce(&mut self, board: &Board, mut alpha: i32, beta: i32) -> i32 {
+        if self.out_of_time() {
+            return 0;
+        }
+        let stand_pat = Self::evaluate(board);
+        if stand_pat >= beta {
+            return stand_pat;
+        }
+        alpha = alpha.max(stand_pat);
+
+        let mut generator = MoveGen::new_legal(board);
+        generator.set_iterator_mask(*board.color_combined(!board.side_to_move()));
+        let mut captures: Vec<ChessMove> = generator.collect();
+        Self::order_moves(board, &mut captures, None);
+
+        for chess_move in captures {
+            let score = -self.quiesce(&board.make_move_new(chess_move), -beta, -alpha);
+            if self.stopped {
+                return 0;
+            }
+            if score >= beta {
+                return score;
+            }
+            alpha = alpha.max(score);
+        }
+        alpha
+    }
+
+    /// Counts a node and stops the search once the deadline has passed
+    fn out_of_time(&mut self) -> bool {
+        self.nodes += 1;
+        if self.can_stop && self.nodes.is_multiple_of(TIME_CHECK_NODES) && Instant::now() >= self.deadline {
+            self.stopped = true;
+        }
+        self.stopped
+    }
+
+    /// Transposition table move first, then captures (most valuable victim, least valuable
+    /// attacker), promotions and quiet moves
+    fn order_moves(board: &Board, moves: &mut [ChessMove], tt_move: Option<ChessMove>) {
+        moves.sort_by_cached_key(|chess_move| {
+            if Some(*chess_move) == tt_move {
+                return i32::MIN;
+            }
+            let mut key = 0;
+            if let Some(victim) = board.piece_on(chess_move.get_dest()) {
+                let attacker = board.piece_on(chess_move.get_source()).map_or(0, Self::piece_value);
+                key -= 10 * Self::piece_value(victim) - attacker;
+            }
+            if let Some(promotion) = chess_move.get_promotion() {
+                key -= Self::piece_value(promotion);
+            }
+            key
+        });
+    }
+
+    /// Static evaluation from the side to move's point of view
+    fn evaluate(board: &Board) -> i32 {
+        let minor_and_major = *board.pieces(Piece::Knight) | *board.pieces(Piece::Bishop) | *board.pieces(Piece::Rook);
+        let endgame = board.pieces(Piece::Queen).popcnt() == 0 || minor_and_major.popcnt() <= 2;
+
+        let mut score = 0;
+        for color in [Color::White, Color::Black] {
+            let sign = if color == Color::White { 1 } else { -1 };
+            for piece in ALL_PIECES {
+                let pieces: BitBoard = *board.pieces(piece) & *board.color_combined(color);
+                for square in pieces {
+                    let rank = square.get_rank().to_index();
+                    let file = square.get_file().to_index();
+                    // Tables are drawn from White's side, rank 8 first
+                    let index = if color == Color::White { (7 - rank) * 8 + file } else { rank * 8 + file };
+                    score += sign * (Self::piece_value(piece) + Self::square_bonus(piece, index, endgame));
+                }
+            }
+        }
+        if board.side_to_move() == Color::White { score } else { -score }
+    }
+
+    fn piece_value(piece: Piece) -> i32 {
+        PIECE_VALUES[piece.to_index()]
+    }
+
+    fn square_bonus(piece: Piece, index: usize, endgame: bool) -> i32 {
+        match piece {
+            Piece::Pawn => PAWN_TABLE[index],
+            Piece::Knight => KNIGHT_TABLE[index],
+            Piece::Bishop => BISHOP_TABLE[index],
+            Piece::Rook => ROOK_TABLE[index],
+            Piece::Queen => QUEEN_TABLE[index],
+            Piece::King if endgame => KING_ENDGAME_TABLE[index],
+            Piece::King => KING_MIDDLEGAME_TABLE[index],
+        }
+    }
+
+    // Mate scores are stored relative to the node so they stay right from any ply
+    fn score_to_tt(score: i32, ply: i32) -> i32 {
+        if score > MATE_THRESHOLD {
+            score + ply
+        } else if score < -MATE_THRESHOLD {
+            score - ply
+        } else {
+            score
+        }
+    }
+
+    fn score_from_tt(score: i32, ply: i32) -> i32 {
+        if score > MATE_THRESHOLD {
+            score - ply
+        } else if score < -MATE_THRESHOLD {
+            score + ply
+        } else {
+            score
+        }
+    }
+}
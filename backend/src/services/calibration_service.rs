@@ -0,0 +1,562 @@
+use std::sync::Arc;
+use chrono::{DateTime, Utc};
+use tokio::sync::Semaphore;
+use tokio::task::JoinSet;
+use tracing::info;
+use crate::models::{DifficultyProfile, Personality};
+use crate::services::{ChessService, DifficultyProfiles, EngineRegistry};
+use crate::services::chess_service::STARTING_FEN;
+use crate::services::engines::{move_seed, DEFAULT_ENGINE, MAX_SEED};
+use rand::Rng;
+
+/// Games each pair of players plays when --games is not given (colours alternate)
+const DEFAULT_GAMES_PER_PAIR: u32 = 10;
+
+/// Plies after which a game is scored as a draw
+const DEFAULT_MAX_PLIES: u32 = 300;
+
+/// Games played at the same time when --concurrency is not given (the default pool size)
+const DEFAULT_CONCURRENCY: usize = 2;
+
+/// Thinking time of the reference players
+const DEFAULT_REFERENCE_MOVETIME_MS: i32 = 100;
+
+/// Stockfish's UCI_Elo range; the references are anchored on its rating scale
+const REFERENCE_ELO_RANGE: std::ops::RangeInclusive<i32> = 1320..=3190;
+
+/// Rating the players are centred on when no reference player anchors the scale
+const UNANCHORED_MEAN_ELO: f64 = 1500.0;
+
+/// Openings played in turn (each with both colours) so strong, deterministic levels
+/// don't repeat the same game
+const OPENINGS: [&str; 8] = [
+    "",
+    "e2e4 e7e5",
+    "d2d4 d7d5",
+    "e2e4 c7c5",
+    "c2c4 e7e5",
+    "d2d4 g8f6 c2c4 e7e6",
+    "e2e4 e7e6",
+    "g1f3 d7d5",
+];
+
+/// One side of the calibration games
+#[derive(Debug, Clone)]
+pub struct CalibrationPlayer {
+    pub name: String,
+    pub profile: DifficultyProfile,
+    /// Engine playing the player's moves (registry name)
+    pub engine: String,
+    /// Rating the player is supposed to have (`difficulty * 100` for levels)
+    pub claimed_elo: i32,
+    /// Known rating of a reference player, which anchors the others
+    pub anchor_elo: Option<f64>,
+}
+
+/// Settings of a calibration run
+#[derive(Debug, Clone)]
+pub struct CalibrationConfig {
+    pub players: Vec<CalibrationPlayer>,
+    pub games_per_pair: u32,
+    pub max_plies: u32,
+    /// Games played at the same time (each needs a Stockfish process per move)
+    pub concurrency: usize,
+    /// Markdown report file (printed only when None)
+    pub output: Option<String>,
+}
+
+/// Result of one calibration game
+#[derive(Debug, Clone)]
+pub struct CalibrationGame {
+    pub white: usize,
+    pub black: usize,
+    /// 1 for a White win, 0.5 for a draw, 0 for a Black win
+    pub white_score: f64,
+    pub plies: u32,
+    /// Checkmate, draw rule or "move_limit"
+    pub termination: String,
+}
+
+/// Estimated strength of a player
+#[derive(Debug, Clone)]
+pub struct PlayerRating {
+    pub name: String,
+    pub claimed_elo: i32,
+    pub elo: f64,
+    /// Half-width of the 95% confidence interval (None for reference players)
+    pub margin_95: Option<f64>,
+    pub games: u32,
+    pub points: f64,
+}
+
+/// Outcome of a calibration run
+#[derive(Debug, Clone)]
+pub struct CalibrationReport {
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub players: Vec<CalibrationPlayer>,
+    pub games: Vec<CalibrationGame>,
+    pub ratings: Vec<PlayerRating>,
+}
+
+/// Service measuring the real strength of the difficulty levels with engine-vs-engine games
+///
+/// Players move with the engine `EngineRegistry` picks for their level, exactly like the bot
+/// in a real game, so the engine and the weaknesses of their profiles are what is measured.
+pub struct CalibrationService;
+
+impl CalibrationService {
+    /// Runs the `calibrate` command: plays the games, prints the report and writes it to
+    /// the --output file
+    ///
+    /// Options: `--levels 1,5,10` (profile names, default the 20 levels), `--references
+    /// 1320,1600,2000` (Stockfish UCI_Elo anchors, none by default), `--games N` per pair,
+    /// `--max-plies N`, `--concurrency N`, `--output report.md`.
+    pub async fn run_cli(args: &[String]) -> Result<(), String> {
+        let config = Self::parse_args(args, &DifficultyProfiles::global())?;
+        let report = Self::run(&config).await?;
+        let text = Self::render_report(&report, &config);
+        println!("{}", text);
+        if let Some(path) = &config.output {
+            std::fs::write(path, &text).map_err(|e| format!("Cannot write report {}: {}", path, e))?;
+            info!("📝 Calibration report written to {}", path);
+        }
+        Ok(())
+    }
+
+    fn parse_args(args: &[String], profiles: &DifficultyProfiles) -> Result<CalibrationConfig, String> {
+        let mut levels: Vec<String> = (1..=20).map(|level| level.to_string()).collect();
+        let mut references: Vec<i32> = Vec::new();
+        let mut config = CalibrationConfig {
+            players: Vec::new(),
+            games_per_pair: DEFAULT_GAMES_PER_PAIR,
+            max_plies: DEFAULT_MAX_PLIES,
+            concurrency: DEFAULT_CONCURRENCY,
+            output: None,
+        };
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().cloned().ok_or_else(|| format!("Missing value for {}", arg));
+            let number = |value: String| value.parse::<u32>().map_err(|_| format!("Invalid value for {}: {}", arg, value));
+            match arg.as_str() {
+                "--levels" => levels = value()?.split(',').map(|name| name.trim().to_string()).collect(),
+                "--references" => {
+                    references = value()?
+                        .split(',')
+                        .map(|elo| elo.trim().parse::<i32>().map_err(|_| format!("Invalid reference Elo: {}", elo)))
+                        .collect::<Result<_, _>>()?;
+                }
+                "--games" => config.games_per_pair = number(value()?)?.max(1),
+                "--max-plies" => config.max_plies = number(value()?)?.max(1),
+                "--concurrency" => config.concurrency = (number(value()?)? as usize).max(1),
+                "--output" => config.output = Some(value()?),
+                other => return Err(format!("Unknown calibrate option: {}", other)),
+            }
+        }
+
+        let engines = EngineRegistry::global();
+        for name in levels {
+            let profile = profiles
+                .get(&name)
+                .cloned()
+                .ok_or_else(|| format!("Unknown difficulty profile: {}", name))?;
+            config.players.push(CalibrationPlayer {
+                name: format!("Profile {}", profile.name),
+                claimed_elo: profile.level * 100,
+                engine: engines.engine_for_new_game(profile.level, None)?,
+                profile,
+                anchor_elo: None,
+            });
+        }
+        for elo in references {
+            config.players.push(Self::reference_player(elo)?);
+        }
+        if config.players.len() < 2 {
+            return Err("Calibration needs at least two players".to_string());
+        }
+        Ok(config)
+    }
+
+    /// Full-strength Stockfish limited to a rating with UCI_Elo, used as a fixed anchor
+    ///
+    /// # Returns
+    /// Result<CalibrationPlayer, String> - Reference player or error when the rating is out of range
+    pub fn reference_player(elo: i32) -> Result<CalibrationPlayer, String> {
+        if !REFERENCE_ELO_RANGE.contains(&elo) {
+            return Err(format!(
+                "Invalid reference Elo: {} (expected {} to {})",
+                elo,
+                REFERENCE_ELO_RANGE.start(),
+                REFERENCE_ELO_RANGE.end()
+            ));
+        }
+        Ok(CalibrationPlayer {
+            name: format!("Stockfish {}", elo),
+            profile: DifficultyProfile {
+                name: format!("reference-{}", elo),
+                level: 20,
+                skill_level: 20,
+                movetime_ms: DEFAULT_REFERENCE_MOVETIME_MS,
+                uci_elo: Some(elo),
+                multipv: 1,
+                random_move_chance: 0.0,
+                blunder_chance: 0.0,
+                any_candidate_chance: 0.0,
+                weak_candidate_chance: 0.0,
+                near_best_chance: 0.0,
+                updated_at: Utc::now(),
+            },
+            // UCI_Elo is a Stockfish option
+            engine: DEFAULT_ENGINE.to_string(),
+            claimed_elo: elo,
+            anchor_elo: Some(elo as f64),
+        })
+    }
+
+    /// Plays every pair of players against each other and estimates their ratings
+    ///
+    /// # Returns
+    /// Result<CalibrationReport, String> - Games and ratings, or the first engine error
+    pub async fn run(config: &CalibrationConfig) -> Result<CalibrationReport, String> {
+        let started_at = Utc::now();
+        let players = Arc::new(config.players.clone());
+        let permits = Arc::new(Semaphore::new(config.concurrency));
+        let mut tasks = JoinSet::new();
+
+        for first in 0..players.len() {
+            for second in first + 1..players.len() {
+                for round in 0..config.games_per_pair {
+                    // Each opening is played twice in a row, once with each colour
+                    let (white, black) = if round % 2 == 0 { (first, second) } else { (second, first) };
+                    let opening = OPENINGS[(round as usize / 2) % OPENINGS.len()];
+                    let players = players.clone();
+                    let permits = permits.clone();
+                    let max_plies = config.max_plies;
+                    tasks.spawn(async move {
+                        let _permit = permits.acquire_owned().await.map_err(|e| e.to_string())?;
+                        Self::play_game(&players, white, black, opening, max_plies).await
+                    });
+                }
+            }
+        }
+
+        let total = tasks.len();
+        let mut games = Vec::with_capacity(total);
+        while let Some(joined) = tasks.join_next().await {
+            let game = joined.map_err(|e| format!("Calibration game failed: {}", e))??;
+            info!(
+                "♟️ [{}/{}] {} - {}: {} ({}, {} plies)",
+                games.len() + 1,
+                total,
+                players[game.white].name,
+                players[game.black].name,
+                Self::score_text(game.white_score),
+                game.termination,
+                game.plies
+            );
+            games.push(game);
+        }
+
+        let ratings = Self::estimate_ratings(&players, &games);
+        Ok(CalibrationReport {
+            started_at,
+            finished_at: Utc::now(),
+            players: players.to_vec(),
+            games,
+            ratings,
+        })
+    }
+
+    /// Plays one game from an opening (moves in coordinate notation)
+    async fn play_game(
+        players: &[CalibrationPlayer],
+        white: usize,
+        black: usize,
+        opening: &str,
+        max_plies: u32,
+    ) -> Result<CalibrationGame, String> {
+        let seed = rand::thread_rng().gen_range(0..=MAX_SEED);
+        let mut fen = STARTING_FEN.to_string();
+        let mut history: Vec<String> = Vec::new();
+        let mut plies = 0;
+        let mut white_to_move = true;
+
+        let mut opening_moves = opening.split_whitespace();
+        loop {
+            let (over, winner, draw_reason) = ChessService::check_game_status(&fen, &history)?;
+            if over {
+                let white_score = match winner.as_deref() {
+                    Some("white") => 1.0,
+                    Some("black") => 0.0,
+                    _ => 0.5,
+                };
+                let termination = draw_reason.unwrap_or_else(|| "checkmate".to_string());
+                return Ok(CalibrationGame { white, black, white_score, plies, termination });
+            }
+            if plies >= max_plies {
+                return Ok(CalibrationGame { white, black, white_score: 0.5, plies, termination: "move_limit".to_string() });
+            }
+
+            let chess_move = match opening_moves.next() {
+                Some(chess_move) => chess_move.to_string(),
+                None => {
+                    let player = &players[if white_to_move { white } else { black }];
+                    EngineRegistry::global()
+                        .engine(&player.engine)
+                        .best_move(&fen, &player.profile, Personality::Balanced, move_seed(seed, plies as i32))
+                        .await
+                        .map_err(|e| format!("{}: {}", player.name, e))?
+                }
+            };
+            if let Some(key) = ChessService::position_key(&fen) {
+                history.push(key);
+            }
+            fen = ChessService::make_move(&fen, &chess_move)?;
+            plies += 1;
+            white_to_move = !white_to_move;
+        }
+    }
+
+    /// Maximum-likelihood ratings (logistic Elo model, draws counting half a win)
+    ///
+    /// Reference players keep their rating and anchor the others; without any, ratings are
+    /// relative and centred on 1500. A draw is added to every pairing so that a perfect
+    /// score still gives a finite rating. The 95% interval only accounts for the number of
+    /// games played, not for the uncertainty of the opponents' ratings.
+    fn estimate_ratings(players: &[CalibrationPlayer], games: &[CalibrationGame]) -> Vec<PlayerRating> {
+        let count = players.len();
+        let mut played = vec![vec![0.0_f64; count]; count];
+        let mut points = vec![vec![0.0_f64; count]; count];
+        for game in games {
+            played[game.white][game.black] += 1.0;
+            played[game.black][game.white] += 1.0;
+            points[game.white][game.black] += game.white_score;
+            points[game.black][game.white] += 1.0 - game.white_score;
+        }
+        for i in 0..count {
+            for j in 0..count {
+                if i != j && played[i][j] > 0.0 {
+                    played[i][j] += 1.0;
+                    points[i][j] += 0.5;
+                }
+            }
+        }
+
+        let anchored = players.iter().any(|player| player.anchor_elo.is_some());
+        let scale = 400.0 / std::f64::consts::LN_10;
+        let expected = |ri: f64, rj: f64| 1.0 / (1.0 + 10f64.powf((rj - ri) / 400.0));
+        let mut ratings: Vec<f64> = players
+            .iter()
+            .map(|player| player.anchor_elo.unwrap_or(UNANCHORED_MEAN_ELO))
+            .collect();
+
+        for _ in 0..1000 {
+            let mut largest_step: f64 = 0.0;
+            for i in 0..count {
+                if players[i].anchor_elo.is_some() {
+                    continue;
+                }
+                let (mut surplus, mut information) = (0.0, 0.0);
+                for j in 0..count {
+                    if played[i][j] > 0.0 {
+                        let e = expected(ratings[i], ratings[j]);
+                        surplus += points[i][j] - played[i][j] * e;
+                        information += played[i][j] * e * (1.0 - e);
+                    }
+                }
+                if information > 0.0 {
+                    // Newton step on the log-likelihood, capped to keep early iterations stable
+                    let step = (scale * surplus / information).clamp(-200.0, 200.0);
+                    ratings[i] += step;
+                    largest_step = largest_step.max(step.abs());
+                }
+            }
+            if !anchored {
+                let shift = UNANCHORED_MEAN_ELO - ratings.iter().sum::<f64>() / count as f64;
+                ratings.iter_mut().for_each(|rating| *rating += shift);
+            }
+            if largest_step < 0.01 {
+                break;
+            }
+        }
+
+        players
+            .iter()
+            .enumerate()
+            .map(|(i, player)| {
+                let information: f64 = (0..count)
+                    .filter(|j| played[i][*j] > 0.0)
+                    .map(|j| {
+                        let e = expected(ratings[i], ratings[j]);
+                        played[i][j] * e * (1.0 - e)
+                    })
+                    .sum();
+                let real_games = games.iter().filter(|game| game.white == i || game.black == i);
+                PlayerRating {
+                    name: player.name.clone(),
+                    claimed_elo: player.claimed_elo,
+                    elo: ratings[i],
+                    margin_95: (player.anchor_elo.is_none() && information > 0.0)
+                        .then(|| 1.96 * scale / information.sqrt()),
+                    games: real_games.clone().count() as u32,
+                    points: real_games
+                        .map(|game| if game.white == i { game.white_score } else { 1.0 - game.white_score })
+                        .sum(),
+                }
+            })
+            .collect()
+    }
+
+    /// Markdown report: ratings against the claimed ones, then the score of every pairing
+    pub fn render_report(report: &CalibrationReport, config: &CalibrationConfig) -> String {
+        let mut text = String::new();
+        text.push_str("# Difficulty calibration\n\n");
+        text.push_str(&format!(
+            "{} games from {} to {} ({} per pairing, draw after {} plies).\n\n",
+            report.games.len(),
+            report.started_at.format("%Y-%m-%d %H:%M UTC"),
+            report.finished_at.format("%Y-%m-%d %H:%M UTC"),
+            config.games_per_pair,
+            config.max_plies
+        ));
+        if report.players.iter().all(|player| player.anchor_elo.is_none()) {
+            text.push_str("No reference player: ratings are relative, centred on 1500.\n\n");
+        }
+
+        text.push_str("| Player | Engine | Claimed Elo | Estimated Elo | 95% interval | Games | Score |\n");
+        text.push_str("|---|---|---:|---:|---:|---:|---:|\n");
+        let engines = EngineRegistry::global();
+        for (rating, player) in report.ratings.iter().zip(&report.players) {
+            let interval = match rating.margin_95 {
+                Some(margin) => format!("±{:.0}", margin),
+                None => "reference".to_string(),
+            };
+            let percent = if rating.games > 0 { 100.0 * rating.points / rating.games as f64 } else { 0.0 };
+            text.push_str(&format!(
+                "| {} | {} | {} | {:.0} | {} | {} | {} ({:.0}%) |\n",
+                rating.name,
+                engines.engine(&player.engine).display_name(),
+                rating.claimed_elo,
+                rating.elo,
+                interval,
+                rating.games,
+                Self::score_text(rating.points),
+                percent
+            ));
+        }
+
+        text.push_str("\n## Pairings\n\n| Player | Opponent | Score | Checkmates | Draws |\n|---|---|---:|---:|---:|\n");
+        let count = report.players.len();
+        for first in 0..count {
+            for second in first + 1..count {
+                let pairing: Vec<&CalibrationGame> = report
+                    .games
+                    .iter()
+                    .filter(|game| (game.white, game.black) == (first, second) || (game.white, game.black) == (second, first))
+                    .collect();
+                if pairing.is_empty() {
+                    continue;
+                }
+                let score: f64 = pairing
+                    .iter()
+                    .map(|game| if game.white == first { game.white_score } else { 1.0 - game.white_score })
+                    .sum();
+                let checkmates = pairing.iter().filter(|game| game.termination == "checkmate").count();
+                let draws = pairing.iter().filter(|game| game.white_score == 0.5).count();
+                text.push_str(&format!(
+                    "| {} | {} | {} / {} | {} | {} |\n",
+                    report.players[first].name,
+                    report.players[second].name,
+                    Self::score_text(score),
+                    pairing.len(),
+                    checkmates,
+                    draws
+                ));
+            }
+        }
+        text
+    }
+
+    /// Points as chess players write them (e.g., "6½")
+    fn score_text(points: f64) -> String {
+        let whole = points.floor();
+        match (whole as i64, points - whole >= 0.5) {
+            (0, true) => "½".to_string(),
+            (whole, true) => format!("{}½", whole),
+            (whole, false) => whole.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Level player, or a reference anchored on `anchor`
+    fn player(name: &str, anchor: Option<i32>) -> CalibrationPlayer {
+        let mut player = CalibrationService::reference_player(anchor.unwrap_or(1500)).unwrap();
+        player.name = name.to_string();
+        player.anchor_elo = anchor.map(f64::from);
+        player
+    }
+
+    /// Games of one pairing, colours alternating, with the first player's score in each
+    fn pairing(first: usize, second: usize, scores: &[f64]) -> Vec<CalibrationGame> {
+        scores
+            .iter()
+            .enumerate()
+            .map(|(index, score)| {
+                let (white, black, white_score) = if index % 2 == 0 { (first, second, *score) } else { (second, first, 1.0 - score) };
+                CalibrationGame { white, black, white_score, plies: 80, termination: "checkmate".to_string() }
+            })
+            .collect()
+    }
+
+    /// Rating gap for a score of `points` out of `games`, after the added draw
+    fn gap(points: f64, games: f64) -> f64 {
+        let score = (points + 0.5) / (games + 1.0);
+        400.0 * (score / (1.0 - score)).log10()
+    }
+
+    fn assert_near(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 0.5, "{} instead of {}", actual, expected);
+    }
+
+    #[test]
+    fn unanchored_ratings_are_centred() {
+        let players = [player("Level 5", None), player("Level 3", None)];
+        // 7.5 / 10 for level 5
+        let games = pairing(0, 1, &[1.0, 1.0, 0.5, 1.0, 0.0, 1.0, 0.5, 1.0, 1.0, 0.5]);
+        let ratings = CalibrationService::estimate_ratings(&players, &games);
+
+        assert_near(ratings[0].elo - ratings[1].elo, gap(7.5, 10.0));
+        assert_near((ratings[0].elo + ratings[1].elo) / 2.0, UNANCHORED_MEAN_ELO);
+        assert_eq!(ratings[0].games, 10);
+        assert_eq!(ratings[0].points, 7.5);
+        assert_eq!(ratings[1].points, 2.5);
+        assert!(ratings.iter().all(|rating| rating.margin_95.is_some_and(|margin| margin > 0.0)));
+    }
+
+    #[test]
+    fn references_anchor_the_levels() {
+        let players = [player("Stockfish 2000", Some(2000)), player("Level 12", None)];
+        // 2.5 / 10 for the level
+        let games = pairing(1, 0, &[0.0, 0.5, 0.0, 1.0, 0.0, 0.0, 0.5, 0.0, 0.5, 0.0]);
+        let ratings = CalibrationService::estimate_ratings(&players, &games);
+
+        assert_eq!(ratings[0].elo, 2000.0);
+        assert_eq!(ratings[0].margin_95, None);
+        assert_near(ratings[1].elo, 2000.0 - gap(7.5, 10.0));
+    }
+
+    #[test]
+    fn perfect_score_gives_a_finite_rating() {
+        let players = [player("Level 20", None), player("Level 1", None)];
+        let games = pairing(0, 1, &[1.0; 10]);
+        let ratings = CalibrationService::estimate_ratings(&players, &games);
+
+        assert!(ratings.iter().all(|rating| rating.elo.is_finite() && rating.margin_95.is_some_and(f64::is_finite)));
+        assert_near(ratings[0].elo - ratings[1].elo, gap(10.0, 10.0));
+    }
+}
@@ -1,28 +1,161 @@
-use chess::{Board, ChessMove, Color, MoveGen, Square, Piece};
+use chess::{Board, BoardBuilder, ChessMove, Color, File, MoveGen, Square, Piece};
 use std::str::FromStr;
+use crate::models::{LegalMoves, MoveDetails, SquareMoves};
+
+/// Standard starting position
+pub const STARTING_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
 
 /// Service responsible for chess game logic and move validation
 pub struct ChessService;
 
 impl ChessService {
-    /// Parses and validates a chess move in algebraic notation
+    /// Parses and validates a chess move in coordinate or Standard Algebraic Notation
     /// 
     /// # Arguments
     /// * `fen` - Current board position in FEN notation
-    /// * `move_str` - Move in algebraic notation (e.g., "e2e4", "Nf3")
+    /// * `move_str` - Move in coordinate notation (e.g., "e2e4", "e7e8q") or SAN
+    ///   (e.g., "Nf3", "exd5", "Nbd2", "O-O", "e8=Q+")
     /// 
     /// # Returns
     /// Result<ChessMove, String> - Valid move or error message
     pub fn parse_move(fen: &str, move_str: &str) -> Result<ChessMove, String> {
-        let board = Board::from_str(fen).map_err(|e| format!("Invalid FEN: {}", e))?;
+        let board = Self::load_board(fen)?;
+        let move_str = move_str.trim();
+
+        if !Self::is_coordinate_move(move_str) {
+            return Self::parse_san(&board, fen, move_str);
+        }
         
-        let chess_move = ChessMove::from_str(move_str)
+        // Chess960 castling is written as the king capturing its own rook (e.g., "b1a1")
+        let chess_move = ChessMove::from_str(&move_str.to_lowercase())
             .map_err(|e| format!("Invalid move format: {}", e))?;
         
-        if board.legal(chess_move) {
+        if Self::legal_moves(&board, fen).contains(&chess_move) {
             Ok(chess_move)
         } else {
-            Err("Illegal move".to_string())
+            Err(format!("Illegal move: {}", move_str))
+        }
+    }
+
+    /// Returns true for coordinate moves such as "e2e4" or "e7e8q"
+    fn is_coordinate_move(move_str: &str) -> bool {
+        let bytes = move_str.as_bytes();
+        let is_square = |f: u8, r: u8| (b'a'..=b'h').contains(&f) && (b'1'..=b'8').contains(&r);
+        match bytes.len() {
+            4 => is_square(bytes[0], bytes[1]) && is_square(bytes[2], bytes[3]),
+            5 => is_square(bytes[0], bytes[1]) && is_square(bytes[2], bytes[3])
+                && b"qrbnQRBN".contains(&bytes[4]),
+            _ => false,
+        }
+    }
+
+    /// Parses a move in Standard Algebraic Notation against the legal moves of a position
+    /// 
+    /// Check/mate suffixes and annotations ("+", "#", "!", "?") are ignored, "0-0" is
+    /// accepted for castling and the "=" before a promotion piece is optional.
+    fn parse_san(board: &Board, fen: &str, san: &str) -> Result<ChessMove, String> {
+        let text = san
+            .trim_end_matches(['+', '#', '!', '?'])
+            .trim_end_matches("e.p.")
+            .trim()
+            .replace('0', "O");
+
+        if text.is_empty() {
+            return Err("Invalid move format: empty move".to_string());
+        }
+
+        let legal = Self::legal_moves(board, fen);
+
+        if text == "O-O" || text == "O-O-O" {
+            let side = if text == "O-O" { "kingside" } else { "queenside" };
+            return legal
+                .into_iter()
+                .find(|m| Self::castling_side(board, *m) == Some(side))
+                .ok_or_else(|| format!("Illegal move: {} (castling not allowed)", san));
+        }
+
+        let mut chars: Vec<char> = text.chars().collect();
+
+        // Moving piece (pawns have no letter)
+        let piece = match chars.first() {
+            Some('N') => Piece::Knight,
+            Some('B') => Piece::Bishop,
+            Some('R') => Piece::Rook,
+            Some('Q') => Piece::Queen,
+            Some('K') => Piece::King,
+            _ => Piece::Pawn,
+        };
+        if piece != Piece::Pawn {
+            chars.remove(0);
+        }
+
+        // Promotion piece at the end ("e8=Q" or "e8Q")
+        let mut promotion = None;
+        if let Some(last) = chars.last().copied() {
+            let promoted = match last {
+                'Q' => Some(Piece::Queen),
+                'R' => Some(Piece::Rook),
+                'B' => Some(Piece::Bishop),
+                'N' => Some(Piece::Knight),
+                _ => None,
+            };
+            if promoted.is_some() {
+                if piece != Piece::Pawn {
+                    return Err(format!("Invalid move format: {}", san));
+                }
+                promotion = promoted;
+                chars.pop();
+                if chars.last() == Some(&'=') {
+                    chars.pop();
+                }
+            }
+        }
+
+        // Destination square is always the last two characters
+        if chars.len() < 2 {
+            return Err(format!("Invalid move format: {}", san));
+        }
+        let dest_text: String = chars.split_off(chars.len() - 2).into_iter().collect();
+        let dest = Square::from_str(&dest_text)
+            .map_err(|_| format!("Invalid move format: {}", san))?;
+
+        // Whatever remains is disambiguation, optionally followed by "x"
+        if chars.last() == Some(&'x') {
+            chars.pop();
+        }
+        let mut from_file = None;
+        let mut from_rank = None;
+        for c in chars {
+            match c {
+                'a'..='h' if from_file.is_none() && from_rank.is_none() => from_file = Some(c as usize - 'a' as usize),
+                '1'..='8' if from_rank.is_none() => from_rank = Some(c as usize - '1' as usize),
+                _ => return Err(format!("Invalid move format: {}", san)),
+            }
+        }
+
+        let candidates: Vec<ChessMove> = legal
+            .into_iter()
+            .filter(|m| {
+                let from = m.get_source();
+                m.get_dest() == dest
+                    && board.piece_on(from) == Some(piece)
+                    && m.get_promotion() == promotion
+                    && Self::castling_side(board, *m).is_none()
+                    && from_file.is_none_or(|f| from.get_file().to_index() == f)
+                    && from_rank.is_none_or(|r| from.get_rank().to_index() == r)
+            })
+            .collect();
+
+        match candidates.as_slice() {
+            [chess_move] => Ok(*chess_move),
+            [] if piece == Piece::Pawn && promotion.is_none() && [0, 7].contains(&dest.get_rank().to_index()) => {
+                Err(format!("Illegal move: {} (promotion piece required, e.g. {}=Q)", san, text))
+            },
+            [] => Err(format!("Illegal move: {}", san)),
+            _ => {
+                let options: Vec<String> = candidates.iter().map(|m| Self::san(board, fen, *m)).collect();
+                Err(format!("Ambiguous move: {} (could be {})", san, options.join(", ")))
+            }
         }
     }
 
@@ -36,7 +169,6 @@ impl ChessService {
 	/// # Returns
 	/// Option<String> - En passant target square in algebraic notation or None
 	fn en_passant_target(from: &Square, to: &Square, piece: Option<Piece>) -> Option<String> {
-        use std::str::FromStr;
         if piece != Some(Piece::Pawn) {
             return None;
         }
@@ -56,118 +188,317 @@ impl ChessService {
     /// 
     /// # Arguments
     /// * `fen` - Current board position in FEN notation
-    /// * `move_str` - Move in algebraic notation
+    /// * `move_str` - Move in coordinate notation or SAN
     /// 
     /// # Returns
     /// Result<String, String> - New FEN position or error message
 	pub fn make_move(fen: &str, move_str: &str) -> Result<String, String> {
-        let mut board = Board::from_str(fen).map_err(|e| format!("Invalid FEN: {}", e))?;
+        let board = Self::load_board(fen)?;
         
         println!("🎯 Processing move: {}", move_str);
         
-        // Parse the move - handle promotion
-        let chess_move = if move_str.len() == 5 {
-            // Promotion move (e.g., "e7e8q")
-            let from_square = Square::from_str(&move_str[0..2])
-                .map_err(|_| format!("Invalid source square: {}", &move_str[0..2]))?;
-            let to_square = Square::from_str(&move_str[2..4])
-                .map_err(|_| format!("Invalid target square: {}", &move_str[2..4]))?;
-            
-            let promotion_char = move_str.chars().nth(4).unwrap();
-            let promotion_piece = match promotion_char {
-                'q' => Some(Piece::Queen),
-                'r' => Some(Piece::Rook),
-                'b' => Some(Piece::Bishop),
-                'n' => Some(Piece::Knight),
-                _ => return Err(format!("Invalid promotion piece: {}", promotion_char))
-            };
-            
-            println!("🎯 Promotion detected: {} to {} promoting to {:?}", 
-                     from_square, to_square, promotion_piece);
-            
-            ChessMove::new(from_square, to_square, promotion_piece)
-        } else {
-            // Regular move (e.g., "e2e4")
-            ChessMove::from_str(move_str)
-                .map_err(|e| format!("Invalid move format: {}", e))?
-        };
+        // Parse and validate the move (coordinate notation or SAN)
+        let chess_move = Self::parse_move(fen, move_str)?;
         
         let from_square = chess_move.get_source();
 		let to_square = chess_move.get_dest();
 
-		// Check legality without consuming the ChessMove
-		let legal = MoveGen::new_legal(&board).any(|m| m == chess_move);
-		if !legal {
-			return Err(format!("Illegal move: {}", move_str));
-		}
-
-		// Determine moved piece before applying the move
+		// Determine moved piece and capture before applying the move
         let moved_piece = board.piece_on(from_square);
+        let is_capture = Self::castling_side(&board, chess_move).is_none() && board.piece_on(to_square).is_some();
+        let mover = board.side_to_move();
         
-        board = board.make_move_new(chess_move);
+        let after = Self::play(&board, chess_move)?;
         println!("✅ Move applied successfully");
 
+		// The chess crate always prints "0 1" as move counters, so maintain them ourselves
+        let halfmove_clock = if moved_piece == Some(Piece::Pawn) || is_capture {
+            0
+        } else {
+            Self::halfmove_clock(fen) + 1
+        };
+        let fullmove_number = match mover {
+            Color::White => Self::fullmove_number(fen),
+            Color::Black => Self::fullmove_number(fen) + 1,
+        };
+
 		// Build FEN and ensure en-passant target is correct for double pawn-step
-        let mut fen_after = board.to_string();
-        if let Some(ep) = Self::en_passant_target(&from_square, &to_square, moved_piece) {
-            // replace the 4th field (en-passant) with our computed target if it's different
-            let mut parts: Vec<String> = fen_after.split_whitespace().map(|s| s.to_string()).collect();
-            if parts.len() == 6 && parts[3] != ep {
+        let mut parts: Vec<String> = after.to_string().split_whitespace().map(|s| s.to_string()).collect();
+        if parts.len() == 6 {
+            if Self::is_chess960_fen(fen) {
+                parts[2] = Self::chess960_castling_after(fen, &board, chess_move);
+            }
+            if let Some(ep) = Self::en_passant_target(&from_square, &to_square, moved_piece) {
+                // replace the 4th field (en-passant) with our computed target if it's different
                 parts[3] = ep;
-                fen_after = parts.join(" ");
             }
+            parts[4] = halfmove_clock.to_string();
+            parts[5] = fullmove_number.to_string();
         }
 
-        Ok(fen_after)
+        Ok(parts.join(" "))
     }
 
     /// Checks if the game is over and determines the winner
     /// 
     /// # Arguments
     /// * `fen` - Current board position in FEN notation
+    /// * `history` - Position keys (see `position_key`) of every earlier position of the game
     /// 
     /// # Returns
-    /// (bool, Option<String>) - (is_game_over, winner)
+    /// (bool, Option<String>, Option<String>) - (is_game_over, winner, draw_reason)
     /// - winner: "white", "black", or "draw"
-    pub fn check_game_over(fen: &str) -> (bool, Option<String>) {
-        let board = match Board::from_str(fen) {
-            Ok(board) => board,
-            Err(_) => return (false, None),
-        };
+    /// - draw_reason: "stalemate", "insufficient_material", "fifty_move_rule" or "threefold_repetition"
+    pub fn check_game_over(fen: &str, history: &[String]) -> (bool, Option<String>, Option<String>) {
+        Self::check_game_status(fen, history).unwrap_or((false, None, None))
+    }
+
+    /// Similar to check_game_over but returns a Result for error handling
+    /// 
+    /// # Arguments
+    /// * `fen` - Current board position in FEN notation
+    /// * `history` - Position keys of every earlier position of the game
+    /// 
+    /// # Returns
+    /// Result<(bool, Option<String>, Option<String>), String> - Game status or error
+    pub fn check_game_status(fen: &str, history: &[String]) -> Result<(bool, Option<String>, Option<String>), String> {
+        let board = Self::load_board(fen)?;
         
-        match board.status() {
+        let draw = |reason: &str| Ok((true, Some("draw".to_string()), Some(reason.to_string())));
+
+        match Self::status(&board, fen) {
             chess::BoardStatus::Checkmate => {
                 let winner = match board.side_to_move() {
                     Color::White => "black", // White to move but checkmate = Black wins
                     Color::Black => "white", // Black to move but checkmate = White wins
                 };
-                (true, Some(winner.to_string()))
+                return Ok((true, Some(winner.to_string()), None));
             },
-            chess::BoardStatus::Stalemate => (true, Some("draw".to_string())),
-            chess::BoardStatus::Ongoing => (false, None),
+            chess::BoardStatus::Stalemate => return draw("stalemate"),
+            chess::BoardStatus::Ongoing => {}
         }
+
+        if Self::is_insufficient_material(&board) {
+            return draw("insufficient_material");
+        }
+
+        // 100 half-moves without a capture or pawn move = 50 moves for each side
+        if Self::halfmove_clock(fen) >= 100 {
+            return draw("fifty_move_rule");
+        }
+
+        let key = Self::board_key(&board, fen);
+        let occurrences = 1 + history.iter().filter(|k| **k == key).count();
+        if occurrences >= 3 {
+            return draw("threefold_repetition");
+        }
+
+        Ok((false, None, None))
     }
 
-    /// Similar to check_game_over but returns a Result for error handling
+    /// Validates a starting position supplied by a user
+    /// 
+    /// Checks the FEN structure, piece counts, castling rights, en passant square and that
+    /// the side not to move is not in check, with a descriptive error for each problem.
+    /// Missing move counters default to "0 1".
     /// 
     /// # Arguments
-    /// * `fen` - Current board position in FEN notation
+    /// * `fen` - Position in FEN notation
+    /// * `chess960` - Accept Chess960 castling rights in X-FEN or Shredder-FEN notation
     /// 
     /// # Returns
-    /// Result<(bool, Option<String>), String> - Game status or error
-    pub fn check_game_status(fen: &str) -> Result<(bool, Option<String>), String> {
-        let board = Board::from_str(fen).map_err(|e| format!("Invalid FEN: {}", e))?;
-        
-        match board.status() {
-            chess::BoardStatus::Checkmate => {
-                let winner = match board.side_to_move() {
-                    Color::White => "black",
-                    Color::Black => "white",
+    /// Result<String, String> - Normalized six-field FEN (Shredder-FEN castling rights for
+    /// Chess960) or error message
+    pub fn validate_fen(fen: &str, chess960: bool) -> Result<String, String> {
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        if fields.len() < 4 || fields.len() > 6 {
+            return Err(format!("Invalid FEN: expected 6 fields, found {}", fields.len()));
+        }
+
+        // Piece placement, indexed [rank][file] with rank 0 = first rank
+        let mut grid = [[None::<char>; 8]; 8];
+        let ranks: Vec<&str> = fields[0].split('/').collect();
+        if ranks.len() != 8 {
+            return Err(format!("Invalid FEN: expected 8 ranks, found {}", ranks.len()));
+        }
+        for (i, rank_text) in ranks.iter().enumerate() {
+            let rank = 7 - i;
+            let mut file = 0;
+            for c in rank_text.chars() {
+                match c {
+                    '1'..='8' => file += c as usize - '0' as usize,
+                    'p' | 'n' | 'b' | 'r' | 'q' | 'k' | 'P' | 'N' | 'B' | 'R' | 'Q' | 'K' => {
+                        if file < 8 {
+                            grid[rank][file] = Some(c);
+                        }
+                        file += 1;
+                    },
+                    _ => return Err(format!("Invalid FEN: unexpected character '{}' in rank {}", c, rank + 1)),
+                }
+            }
+            if file != 8 {
+                return Err(format!("Invalid FEN: rank {} describes {} squares instead of 8", rank + 1, file));
+            }
+        }
+
+        let count = |piece: char| grid.iter().flatten().filter(|p| **p == Some(piece)).count();
+        for (king, side) in [('K', "white"), ('k', "black")] {
+            if count(king) != 1 {
+                return Err(format!("Illegal position: {} must have exactly one king, found {}", side, count(king)));
+            }
+        }
+        for (pawn, side) in [('P', "white"), ('p', "black")] {
+            if count(pawn) > 8 {
+                return Err(format!("Illegal position: {} has more than 8 pawns", side));
+            }
+        }
+        for (side, upper) in [("white", true), ("black", false)] {
+            let pieces = grid.iter().flatten().flatten().filter(|p| p.is_ascii_uppercase() == upper).count();
+            if pieces > 16 {
+                return Err(format!("Illegal position: {} has more than 16 pieces", side));
+            }
+        }
+        if grid[0].iter().chain(grid[7].iter()).any(|p| matches!(p, Some('P') | Some('p'))) {
+            return Err("Illegal position: pawns cannot stand on the first or eighth rank".to_string());
+        }
+
+        let find = |piece: char| -> (i32, i32) {
+            for (rank, row) in grid.iter().enumerate() {
+                for (file, square) in row.iter().enumerate() {
+                    if *square == Some(piece) {
+                        return (rank as i32, file as i32);
+                    }
+                }
+            }
+            (-8, -8)
+        };
+        let (white_king, black_king) = (find('K'), find('k'));
+        if (white_king.0 - black_king.0).abs() <= 1 && (white_king.1 - black_king.1).abs() <= 1 {
+            return Err("Illegal position: the kings are on adjacent squares".to_string());
+        }
+
+        let side_to_move = fields[1];
+        if side_to_move != "w" && side_to_move != "b" {
+            return Err(format!("Invalid FEN: side to move must be 'w' or 'b', found '{}'", side_to_move));
+        }
+
+        // Castling rights need the king and the matching rook on their original squares
+        let castling = if chess960 { Self::chess960_castling_rights(&grid, fields[2])? } else { fields[2].to_string() };
+        if castling != "-" && !chess960 {
+            for (i, right) in castling.char_indices() {
+                let (rank, king, rook, rook_file, name) = match right {
+                    'K' => (0, 'K', 'R', 7, "white kingside"),
+                    'Q' => (0, 'K', 'R', 0, "white queenside"),
+                    'k' => (7, 'k', 'r', 7, "black kingside"),
+                    'q' => (7, 'k', 'r', 0, "black queenside"),
+                    _ => return Err(format!("Invalid FEN: unexpected castling right '{}'", right)),
                 };
-                Ok((true, Some(winner.to_string())))
+                if castling[..i].contains(right) {
+                    return Err(format!("Invalid FEN: castling right '{}' is repeated", right));
+                }
+                if grid[rank][4] != Some(king) || grid[rank][rook_file] != Some(rook) {
+                    return Err(format!(
+                        "Illegal position: {} castling requires the king and rook on their original squares",
+                        name
+                    ));
+                }
+            }
+        }
+
+        // En passant target: behind a pawn that just made a double step
+        let en_passant = fields[3];
+        if en_passant != "-" {
+            let target = Square::from_str(en_passant)
+                .map_err(|_| format!("Invalid FEN: invalid en passant square '{}'", en_passant))?;
+            let file = target.get_file().to_index();
+            let (target_rank, pawn_rank, origin_rank, pawn) = if side_to_move == "w" { (5, 4, 6, 'p') } else { (2, 3, 1, 'P') };
+            if target.get_rank().to_index() != target_rank
+                || grid[pawn_rank][file] != Some(pawn)
+                || grid[target_rank][file].is_some()
+                || grid[origin_rank][file].is_some()
+            {
+                return Err(format!("Illegal position: no pawn can be captured en passant on {}", en_passant));
+            }
+        }
+
+        let halfmove = fields.get(4).copied().unwrap_or("0");
+        let fullmove = fields.get(5).copied().unwrap_or("1");
+        if halfmove.parse::<u32>().is_err() {
+            return Err(format!("Invalid FEN: half-move clock must be a number, found '{}'", halfmove));
+        }
+        if fullmove.parse::<u32>().map_or(true, |n| n == 0) {
+            return Err(format!("Invalid FEN: full-move number must be a positive number, found '{}'", fullmove));
+        }
+
+        let normalized = format!("{} {} {} {} {} {}", fields[0], side_to_move, castling, en_passant, halfmove, fullmove);
+
+        // Everything else the chess crate rejects is the side not to move being in check
+        let board = Self::load_board(&normalized).map_err(|_| {
+            let not_to_move = if side_to_move == "w" { "black" } else { "white" };
+            format!("Illegal position: {} is in check but it is not their move", not_to_move)
+        })?;
+
+        match Self::status(&board, &normalized) {
+            chess::BoardStatus::Checkmate => Err("Illegal position: the side to move is already checkmated".to_string()),
+            chess::BoardStatus::Stalemate => Err("Illegal position: the side to move is already stalemated".to_string()),
+            chess::BoardStatus::Ongoing => Ok(normalized),
+        }
+    }
+
+    /// Builds the key used to compare positions for repetition
+    /// 
+    /// Two positions are identical when piece placement, side to move, castling rights
+    /// and en passant possibility match; move counters are ignored.
+    /// 
+    /// # Arguments
+    /// * `fen` - Board position in FEN notation
+    /// 
+    /// # Returns
+    /// Option<String> - Position key or None if the FEN is invalid
+    pub fn position_key(fen: &str) -> Option<String> {
+        Self::load_board(fen).ok().map(|board| Self::board_key(&board, fen))
+    }
+
+    fn board_key(board: &Board, fen: &str) -> String {
+        // The chess crate only keeps an en passant square when a capture is actually possible
+        let text = board.to_string();
+        let mut fields: Vec<&str> = text.split_whitespace().take(4).collect();
+        // Chess960 castling rights are not part of the board (see load_board)
+        if Self::is_chess960_fen(fen) {
+            fields[2] = fen.split_whitespace().nth(2).unwrap_or("-");
+        }
+        fields.join(" ")
+    }
+
+    /// Reads the half-move clock (plies since the last capture or pawn move) from a FEN
+    fn halfmove_clock(fen: &str) -> u32 {
+        fen.split_whitespace().nth(4).and_then(|c| c.parse().ok()).unwrap_or(0)
+    }
+
+    /// Reads the full-move number from a FEN
+    fn fullmove_number(fen: &str) -> u32 {
+        fen.split_whitespace().nth(5).and_then(|c| c.parse().ok()).unwrap_or(1)
+    }
+
+    /// Returns true when neither side can possibly deliver checkmate
+    /// 
+    /// Covers K vs K, K+minor vs K and K+B vs K+B with bishops on the same colour.
+    fn is_insufficient_material(board: &Board) -> bool {
+        let heavy = board.pieces(Piece::Pawn) | board.pieces(Piece::Rook) | board.pieces(Piece::Queen);
+        if heavy.popcnt() > 0 {
+            return false;
+        }
+
+        let knights = board.pieces(Piece::Knight).popcnt();
+        let bishops = *board.pieces(Piece::Bishop);
+        match (knights, bishops.popcnt()) {
+            (0, 0) | (1, 0) | (0, 1) => true,
+            (0, _) => {
+                // Any number of bishops, as long as they all live on squares of one colour
+                let on_light = bishops.filter(|sq| (sq.get_rank().to_index() + sq.get_file().to_index()) % 2 == 1).count();
+                on_light == 0 || on_light == bishops.popcnt() as usize
             },
-            chess::BoardStatus::Stalemate => Ok((true, Some("draw".to_string()))),
-            chess::BoardStatus::Ongoing => Ok((false, None)),
+            _ => false,
         }
     }
 
@@ -179,40 +510,623 @@ impl ChessService {
     /// # Returns
     /// Result<Vec<String>, String> - List of legal moves in algebraic notation or error
     pub fn get_legal_moves(fen: &str) -> Result<Vec<String>, String> {
-        let board = Board::from_str(fen).map_err(|e| format!("Invalid FEN: {}", e))?;
+        let board = Self::load_board(fen)?;
         
-        let movegen = MoveGen::new_legal(&board);
-        let moves: Vec<String> = movegen
+        let moves: Vec<String> = Self::legal_moves(&board, fen)
+            .into_iter()
             .map(|m| m.to_string())
             .collect();
         
         Ok(moves)
     }
 
-    /// Gets the piece type at a specific square
+    /// Lists the legal moves of a position grouped by origin square
     /// 
     /// # Arguments
     /// * `fen` - Current board position in FEN notation
-    /// * `square_notation` - Square in algebraic notation (e.g., "e4")
     /// 
     /// # Returns
-    /// Option<String> - Piece type name or None if empty
-    pub fn get_piece_at_square(fen: &str, square_notation: &str) -> Option<String> {
-        let board = Board::from_str(fen).ok()?;
-        let square = Square::from_str(square_notation).ok()?;
-        
-        if let Some(piece) = board.piece_on(square) {
-            let piece_name = match piece {
-                chess::Piece::Pawn => "pawn",
-                chess::Piece::Knight => "knight", 
-                chess::Piece::Bishop => "bishop",
-                chess::Piece::Rook => "rook",
-                chess::Piece::Queen => "queen",
-                chess::Piece::King => "king",
+    /// Result<LegalMoves, String> - Moves with their full description, check status and king square
+    pub fn legal_moves_by_square(fen: &str) -> Result<LegalMoves, String> {
+        let board = Self::load_board(fen)?;
+
+        let mut squares: Vec<SquareMoves> = Vec::new();
+        for chess_move in Self::legal_moves(&board, fen) {
+            let details = Self::describe_move(fen, &chess_move.to_string())?;
+            let index = match squares.iter().position(|square| square.from == details.from) {
+                Some(index) => index,
+                None => {
+                    squares.push(SquareMoves {
+                        from: details.from.clone(),
+                        piece: details.piece.clone(),
+                        targets: Vec::new(),
+                        moves: Vec::new(),
+                    });
+                    squares.len() - 1
+                }
             };
-            Some(piece_name.to_string())
-        } else {
+            let square = &mut squares[index];
+            if !square.targets.contains(&details.to) {
+                square.targets.push(details.to.clone());
+            }
+            square.moves.push(details);
+        }
+        squares.sort_by(|a, b| a.from.cmp(&b.from));
+
+        let side = board.side_to_move();
+        Ok(LegalMoves {
+            fen: fen.to_string(),
+            side_to_move: Self::color_name(side).to_string(),
+            is_check: board.checkers().popcnt() > 0,
+            king_square: board.king_square(side).to_string(),
+            squares,
+        })
+    }
+
+    fn piece_name(piece: Piece) -> &'static str {
+        match piece {
+            Piece::Pawn => "pawn",
+            Piece::Knight => "knight",
+            Piece::Bishop => "bishop",
+            Piece::Rook => "rook",
+            Piece::Queen => "queen",
+            Piece::King => "king",
+        }
+    }
+
+    fn color_name(color: Color) -> &'static str {
+        match color {
+            Color::White => "white",
+            Color::Black => "black",
+        }
+    }
+
+    /// Describes a move fully: notation, moving piece, capture, check and special moves
+    /// 
+    /// # Arguments
+    /// * `fen` - Board position before the move in FEN notation
+    /// * `move_str` - Move in coordinate notation or SAN
+    /// 
+    /// # Returns
+    /// Result<MoveDetails, String> - Move description or error message
+    pub fn describe_move(fen: &str, move_str: &str) -> Result<MoveDetails, String> {
+        let board = Self::load_board(fen)?;
+        let chess_move = Self::parse_move(fen, move_str)?;
+
+        let from = chess_move.get_source();
+        let to = chess_move.get_dest();
+        let piece = board.piece_on(from).unwrap_or(Piece::Pawn);
+        let file_distance = (from.get_file().to_index() as i32 - to.get_file().to_index() as i32).abs();
+        let castling = Self::castling_side(&board, chess_move);
+
+        // A pawn changing file onto an empty square can only be an en passant capture
+        let is_en_passant = piece == Piece::Pawn && file_distance != 0 && board.piece_on(to).is_none();
+        let captured = if is_en_passant {
+            Some(Piece::Pawn)
+        } else if castling.is_some() {
             None
+        } else {
+            board.piece_on(to)
+        };
+
+        let after = Self::play(&board, chess_move)?;
+
+        // Chess960 castling targets the rook; report the square the king lands on
+        let to = if castling.is_some() { after.king_square(board.side_to_move()) } else { to };
+
+        Ok(MoveDetails {
+            from: from.to_string(),
+            to: to.to_string(),
+            uci: chess_move.to_string(),
+            san: Self::san(&board, fen, chess_move),
+            piece: Self::piece_name(piece).to_string(),
+            color: Self::color_name(board.side_to_move()).to_string(),
+            captured: captured.map(|p| Self::piece_name(p).to_string()),
+            is_check: after.checkers().popcnt() > 0,
+            is_checkmate: after.status() == chess::BoardStatus::Checkmate,
+            castling: castling.map(|side| side.to_string()),
+            is_en_passant,
+            promotion: chess_move.get_promotion().map(|p| Self::piece_name(p).to_string()),
+        })
+    }
+
+    /// Converts a line of moves (e.g., an engine principal variation) to SAN
+    ///
+    /// The conversion stops at the first move that is not legal in the line.
+    ///
+    /// # Arguments
+    /// * `fen` - Position the line starts from
+    /// * `moves` - Moves in coordinate notation
+    pub fn line_to_san(fen: &str, moves: &[String]) -> Vec<String> {
+        let mut position = fen.to_string();
+        let mut sans = Vec::new();
+        for move_str in moves {
+            let (Ok(details), Ok(next)) = (Self::describe_move(&position, move_str), Self::make_move(&position, move_str)) else {
+                break;
+            };
+            sans.push(details.san);
+            position = next;
+        }
+        sans
+    }
+
+    /// Builds the SAN of a legal move on a board
+    fn san(board: &Board, fen: &str, chess_move: ChessMove) -> String {
+        let from = chess_move.get_source();
+        let to = chess_move.get_dest();
+        let piece = board.piece_on(from).unwrap_or(Piece::Pawn);
+
+        let mut san = String::new();
+        let file_distance = (from.get_file().to_index() as i32 - to.get_file().to_index() as i32).abs();
+
+        if let Some(side) = Self::castling_side(board, chess_move) {
+            san.push_str(if side == "kingside" { "O-O" } else { "O-O-O" });
+        } else if piece == Piece::Pawn {
+            // A pawn changing file is always a capture (including en passant)
+            if file_distance != 0 {
+                san.push(Self::file_char(from));
+                san.push('x');
+            }
+            san.push_str(&to.to_string());
+            if let Some(promotion) = chess_move.get_promotion() {
+                san.push('=');
+                san.push_str(&promotion.to_string(Color::White));
+            }
+        } else {
+            san.push_str(&piece.to_string(Color::White));
+
+            // Disambiguate between identical pieces able to reach the same square
+            let rivals: Vec<Square> = Self::legal_moves(board, fen)
+                .into_iter()
+                .filter(|m| m.get_dest() == to && m.get_source() != from && board.piece_on(m.get_source()) == Some(piece))
+                .map(|m| m.get_source())
+                .collect();
+            if !rivals.is_empty() {
+                if rivals.iter().all(|sq| sq.get_file() != from.get_file()) {
+                    san.push(Self::file_char(from));
+                } else if rivals.iter().all(|sq| sq.get_rank() != from.get_rank()) {
+                    san.push_str(&(from.get_rank().to_index() + 1).to_string());
+                } else {
+                    san.push_str(&from.to_string());
+                }
+            }
+
+            if board.piece_on(to).is_some() {
+                san.push('x');
+            }
+            san.push_str(&to.to_string());
+        }
+
+        if let Ok(after) = Self::play(board, chess_move) {
+            if after.status() == chess::BoardStatus::Checkmate {
+                san.push('#');
+            } else if after.checkers().popcnt() > 0 {
+                san.push('+');
+            }
+        }
+
+        san
+    }
+
+    /// Builds the starting position of a Chess960 game from its standard index
+    /// 
+    /// Uses Scharnagl's numbering, so index 518 is the standard starting position.
+    /// 
+    /// # Arguments
+    /// * `index` - Position number, from 0 to 959
+    /// 
+    /// # Returns
+    /// Result<String, String> - Starting FEN with Shredder-FEN castling rights or error message
+    pub fn chess960_position(index: u32) -> Result<String, String> {
+        if index > 959 {
+            return Err(format!("Invalid Chess960 position: {} (expected 0 to 959)", index));
+        }
+
+        // Knight pairs for the last digit, as positions among the five remaining empty files
+        const KNIGHTS: [(usize, usize); 10] = [(0, 1), (0, 2), (0, 3), (0, 4), (1, 2), (1, 3), (1, 4), (2, 3), (2, 4), (3, 4)];
+
+        let mut rank = [' '; 8];
+        let mut n = index as usize;
+        rank[(n % 4) * 2 + 1] = 'B'; // light-squared bishop
+        n /= 4;
+        rank[(n % 4) * 2] = 'B'; // dark-squared bishop
+        n /= 4;
+
+        let empty = |rank: &[char; 8]| -> Vec<usize> { (0..8).filter(|f| rank[*f] == ' ').collect() };
+        let queen = empty(&rank)[n % 6];
+        rank[queen] = 'Q';
+        n /= 6;
+
+        let files = empty(&rank);
+        let (first, second) = KNIGHTS[n];
+        rank[files[first]] = 'N';
+        rank[files[second]] = 'N';
+
+        // The king always stands between the two rooks
+        for (file, piece) in empty(&rank).into_iter().zip(['R', 'K', 'R']) {
+            rank[file] = piece;
+        }
+
+        let white: String = rank.iter().collect();
+        let rooks: Vec<char> = (0..8).rev().filter(|f| rank[*f] == 'R').map(|f| (b'a' + f as u8) as char).collect();
+        let castling: String = rooks.iter().map(|f| f.to_ascii_uppercase()).chain(rooks.iter().copied()).collect();
+
+        Ok(format!("{}/pppppppp/8/8/8/8/PPPPPPPP/{} w {} - 0 1", white.to_lowercase(), white, castling))
+    }
+
+    /// Returns true when a FEN's castling rights name the rooks' files (Shredder-FEN),
+    /// which is how Chess960 positions are stored
+    pub fn is_chess960_fen(fen: &str) -> bool {
+        fen.split_whitespace()
+            .nth(2)
+            .is_some_and(|castling| castling.chars().any(|c| matches!(c, 'A'..='H' | 'a'..='h')))
+    }
+
+    /// Loads a FEN into a chess crate board
+    /// 
+    /// The chess crate only knows standard castling (king on e1/e8, rooks in the corners),
+    /// so Chess960 castling rights are left out of the board and handled here from the FEN.
+    pub(crate) fn load_board(fen: &str) -> Result<Board, String> {
+        let board = if Self::is_chess960_fen(fen) {
+            let mut fields: Vec<&str> = fen.split_whitespace().collect();
+            fields[2] = "-";
+            Board::from_str(&fields.join(" "))
+        } else {
+            Board::from_str(fen)
+        };
+        board.map_err(|e| format!("Invalid FEN: {}", e))
+    }
+
+    /// All legal moves of a position, including Chess960 castling
+    pub(crate) fn legal_moves(board: &Board, fen: &str) -> Vec<ChessMove> {
+        let mut moves: Vec<ChessMove> = MoveGen::new_legal(board).collect();
+        moves.extend(Self::chess960_castling_moves(board, fen));
+        moves
+    }
+
+    /// Board status, counting Chess960 castling the chess crate doesn't generate
+    fn status(board: &Board, fen: &str) -> chess::BoardStatus {
+        match board.status() {
+            chess::BoardStatus::Stalemate if !Self::chess960_castling_moves(board, fen).is_empty() => {
+                chess::BoardStatus::Ongoing
+            },
+            status => status,
+        }
+    }
+
+    /// Returns "kingside" or "queenside" when a legal move is castling
+    /// 
+    /// Standard castling moves the king two files; Chess960 castling is written as the king
+    /// capturing its own rook.
+    fn castling_side(board: &Board, chess_move: ChessMove) -> Option<&'static str> {
+        let from = chess_move.get_source();
+        let to = chess_move.get_dest();
+        if board.piece_on(from) != Some(Piece::King) {
+            return None;
+        }
+
+        let onto_own_rook = board.piece_on(to) == Some(Piece::Rook) && board.color_on(to) == board.color_on(from);
+        let file_distance = (from.get_file().to_index() as i32 - to.get_file().to_index() as i32).abs();
+        if !onto_own_rook && file_distance != 2 {
+            return None;
+        }
+        Some(if to.get_file().to_index() > from.get_file().to_index() { "kingside" } else { "queenside" })
+    }
+
+    /// Plays a legal move, including Chess960 castling
+    pub(crate) fn play(board: &Board, chess_move: ChessMove) -> Result<Board, String> {
+        let from = chess_move.get_source();
+        let to = chess_move.get_dest();
+        let color = board.side_to_move();
+
+        // Standard castling is a two-file king move the chess crate plays itself
+        if board.piece_on(to) != Some(Piece::Rook) || board.color_on(to) != Some(color) {
+            return Ok(board.make_move_new(chess_move));
+        }
+
+        // Chess960 castling: king and rook land on the same squares as in standard chess
+        let rank = color.to_my_backrank();
+        let (king_file, rook_file) = if to.get_file().to_index() > from.get_file().to_index() {
+            (File::G, File::F)
+        } else {
+            (File::C, File::D)
+        };
+        let mut builder = BoardBuilder::from(board);
+        builder
+            .clear_square(from)
+            .clear_square(to)
+            .piece(Square::make_square(rank, king_file), Piece::King, color)
+            .piece(Square::make_square(rank, rook_file), Piece::Rook, color)
+            .side_to_move(!color)
+            .en_passant(None);
+        Board::try_from(builder).map_err(|e| format!("Illegal castling: {}", e))
+    }
+
+    /// Maps a Shredder-FEN castling right to the file index of its rook
+    fn castling_file(right: char) -> Option<usize> {
+        match right.to_ascii_lowercase() {
+            c @ 'a'..='h' => Some(c as usize - 'a' as usize),
+            _ => None,
+        }
+    }
+
+    /// Generates the legal Chess960 castling moves of a position (king takes own rook)
+    /// 
+    /// Every square between the king, the rook and their destinations must be empty apart
+    /// from the king and rook themselves, and the king may not be in check, pass through
+    /// or land on an attacked square.
+    fn chess960_castling_moves(board: &Board, fen: &str) -> Vec<ChessMove> {
+        if !Self::is_chess960_fen(fen) || board.checkers().popcnt() > 0 {
+            return Vec::new();
+        }
+
+        let color = board.side_to_move();
+        let rank = color.to_my_backrank();
+        let king = board.king_square(color);
+        if king.get_rank() != rank {
+            return Vec::new();
+        }
+        let king_file = king.get_file().to_index();
+        let on_rank = |file: usize| Square::make_square(rank, File::from_index(file));
+
+        fen.split_whitespace()
+            .nth(2)
+            .unwrap_or("-")
+            .chars()
+            .filter(|right| right.is_ascii_uppercase() == (color == Color::White))
+            .filter_map(Self::castling_file)
+            .filter_map(|rook_file| {
+                let rook = on_rank(rook_file);
+                if rook_file == king_file || board.piece_on(rook) != Some(Piece::Rook) || board.color_on(rook) != Some(color) {
+                    return None;
+                }
+
+                let (king_to, rook_to) = if rook_file > king_file { (6, 5) } else { (2, 3) };
+                let files = [king_file, rook_file, king_to, rook_to];
+                let (low, high) = (*files.iter().min()?, *files.iter().max()?);
+                let blocked = (low..=high)
+                    .map(on_rank)
+                    .any(|sq| sq != king && sq != rook && board.piece_on(sq).is_some());
+                let attacked = (king_file.min(king_to)..=king_file.max(king_to))
+                    .map(on_rank)
+                    .any(|sq| Self::king_attacked_on(board, sq, &[king, rook]));
+
+                (!blocked && !attacked).then(|| ChessMove::new(king, rook, None))
+            })
+            .collect()
+    }
+
+    /// Returns true when the side to move's king would be attacked on `square`, with the
+    /// `removed` squares emptied first
+    fn king_attacked_on(board: &Board, square: Square, removed: &[Square]) -> bool {
+        let color = board.side_to_move();
+        let mut builder = BoardBuilder::from(board);
+        for sq in removed {
+            builder.clear_square(*sq);
+        }
+        builder.piece(square, Piece::King, color);
+        // An invalid board here means the kings would touch
+        Board::try_from(builder).map_or(true, |b| b.checkers().popcnt() > 0)
+    }
+
+    /// Chess960 castling rights left after a move: a king move loses both of its side's
+    /// rights, and moving or capturing a castling rook loses that right
+    fn chess960_castling_after(fen: &str, board: &Board, chess_move: ChessMove) -> String {
+        let mover = board.side_to_move();
+        let king_moved = board.piece_on(chess_move.get_source()) == Some(Piece::King);
+
+        let rights: String = fen.split_whitespace()
+            .nth(2)
+            .unwrap_or("-")
+            .chars()
+            .filter(|right| {
+                let Some(file) = Self::castling_file(*right) else { return false };
+                let color = if right.is_ascii_uppercase() { Color::White } else { Color::Black };
+                let rook = Square::make_square(color.to_my_backrank(), File::from_index(file));
+                !(king_moved && color == mover) && chess_move.get_source() != rook && chess_move.get_dest() != rook
+            })
+            .collect();
+
+        if rights.is_empty() { "-".to_string() } else { rights }
+    }
+
+    /// Validates Chess960 castling rights and converts them to Shredder-FEN
+    /// 
+    /// Accepts X-FEN ("KQkq" for the outermost rooks, the rook's file otherwise) and
+    /// Shredder-FEN ("HAha"). The grid is indexed [rank][file] as in validate_fen.
+    fn chess960_castling_rights(grid: &[[Option<char>; 8]; 8], castling: &str) -> Result<String, String> {
+        if castling == "-" {
+            return Ok("-".to_string());
+        }
+
+        // (white, rook file, king file) of each right
+        let mut rights: Vec<(bool, usize, usize)> = Vec::new();
+        for right in castling.chars() {
+            let white = right.is_ascii_uppercase();
+            let (rank, king, rook, side) = if white { (0, 'K', 'R', "white") } else { (7, 'k', 'r', "black") };
+            let king_file = grid[rank].iter().position(|p| *p == Some(king)).ok_or_else(|| {
+                format!("Illegal position: {} castling requires the king on its first rank", side)
+            })?;
+            let rooks = (0..8).filter(|f| grid[rank][*f] == Some(rook));
+            let rook_file = match right.to_ascii_lowercase() {
+                'k' => rooks.filter(|f| *f > king_file).max(),
+                'q' => rooks.filter(|f| *f < king_file).min(),
+                'a'..='h' => Self::castling_file(right).filter(|f| grid[rank][*f] == Some(rook)),
+                _ => return Err(format!("Invalid FEN: unexpected castling right '{}'", right)),
+            }
+            .ok_or_else(|| format!("Illegal position: castling right '{}' has no {} rook to castle with", right, side))?;
+
+            if rights.contains(&(white, rook_file, king_file)) {
+                return Err(format!("Invalid FEN: castling right '{}' is repeated", right));
+            }
+            if rights.iter().any(|(w, f, _)| *w == white && (*f > king_file) == (rook_file > king_file)) {
+                return Err(format!("Illegal position: {} has two castling rights on the same side", side));
+            }
+            rights.push((white, rook_file, king_file));
+        }
+
+        // White first, each side from the h-file towards the a-file ("HAha")
+        rights.sort_by_key(|(white, file, _)| (!white, std::cmp::Reverse(*file)));
+        Ok(rights
+            .iter()
+            .map(|(white, file, _)| {
+                let letter = (b'a' + *file as u8) as char;
+                if *white { letter.to_ascii_uppercase() } else { letter }
+            })
+            .collect())
+    }
+
+    fn file_char(square: Square) -> char {
+        (b'a' + square.get_file().to_index() as u8) as char
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Plays moves from a position, returning the final FEN and the keys of every earlier position
+    fn play_moves(fen: &str, moves: &[&str]) -> (String, Vec<String>) {
+        let mut fen = fen.to_string();
+        let mut history = Vec::new();
+        for move_str in moves {
+            history.push(ChessService::position_key(&fen).unwrap());
+            fen = ChessService::make_move(&fen, move_str).unwrap();
+        }
+        (fen, history)
+    }
+
+    fn draw_reason(fen: &str, history: &[String]) -> Option<String> {
+        let (over, winner, reason) = ChessService::check_game_over(fen, history);
+        assert_eq!(over, reason.is_some(), "{}", fen);
+        if over {
+            assert_eq!(winner.as_deref(), Some("draw"));
+        }
+        reason
+    }
+
+    #[test]
+    fn threefold_repetition() {
+        let shuffle = ["Nf3", "Nf6", "Ng1", "Ng8"];
+        let (fen, history) = play_moves(STARTING_FEN, &shuffle);
+        assert_eq!(draw_reason(&fen, &history), None);
+
+        let (fen, history) = play_moves(STARTING_FEN, &[shuffle, shuffle].concat());
+        assert_eq!(draw_reason(&fen, &history).as_deref(), Some("threefold_repetition"));
+    }
+
+    #[test]
+    fn fifty_move_rule() {
+        let fen = "8/8/8/4k3/8/8/R7/4K3 w - - 99 80";
+        assert_eq!(draw_reason(fen, &[]), None);
+
+        let (after_quiet, _) = play_moves(fen, &["Ra3"]);
+        assert_eq!(draw_reason(&after_quiet, &[]).as_deref(), Some("fifty_move_rule"));
+
+        // A pawn move starts the count again
+        let (after_pawn, _) = play_moves("8/8/8/4k3/8/8/P7/4K3 w - - 99 80", &["a3"]);
+        assert_eq!(draw_reason(&after_pawn, &[]), None);
+    }
+
+    #[test]
+    fn insufficient_material() {
+        let insufficient = [
+            "8/8/4k3/8/8/4K3/8/8 w - - 0 1",
+            "8/8/4k3/8/8/4K3/4B3/8 w - - 0 1",
+            "8/8/4k3/8/8/4K3/4N3/8 w - - 0 1",
+            // Bishops on light squares only (e2 and f7)
+            "8/5b2/4k3/8/8/4K3/4B3/8 w - - 0 1",
+        ];
+        for fen in insufficient {
+            assert_eq!(draw_reason(fen, &[]).as_deref(), Some("insufficient_material"), "{}", fen);
         }
+
+        let sufficient = [
+            // Bishops on both colours (e2 and f6)
+            "8/8/4kb2/8/8/4K3/4B3/8 w - - 0 1",
+            "8/8/4k3/8/8/4K3/3NN3/8 w - - 0 1",
+            "8/8/4k3/8/8/4K3/4P3/8 w - - 0 1",
+        ];
+        for fen in sufficient {
+            assert_eq!(draw_reason(fen, &[]), None, "{}", fen);
+        }
+    }
+
+    fn parse(fen: &str, san: &str) -> Result<String, String> {
+        ChessService::parse_move(fen, san).map(|chess_move| chess_move.to_string())
+    }
+
+    #[test]
+    fn ambiguous_san() {
+        // Both knights reach d2
+        let knights = "rnbqkbnr/pppppppp/8/8/3P4/5N2/PPP1PPPP/RNBQKB1R w KQkq - 0 1";
+        let error = parse(knights, "Nd2").unwrap_err();
+        assert!(error.starts_with("Ambiguous move: Nd2"), "{}", error);
+        assert!(error.contains("Nbd2") && error.contains("Nfd2"), "{}", error);
+        assert_eq!(parse(knights, "Nbd2").unwrap(), "b1d2");
+        assert_eq!(parse(knights, "Nfd2").unwrap(), "f3d2");
+
+        // Both rooks reach a3: the rank tells them apart
+        let rooks = "4k3/8/8/R7/8/8/8/R3K3 w - - 0 1";
+        assert!(parse(rooks, "Ra3").unwrap_err().starts_with("Ambiguous move"));
+        assert_eq!(parse(rooks, "R1a3").unwrap(), "a1a3");
+        assert_eq!(parse(rooks, "R5a3+").unwrap(), "a5a3");
+    }
+
+    #[test]
+    fn illegal_san() {
+        for san in ["e5", "Nf6", "Ke2", "O-O", "Bxe7"] {
+            let error = parse(STARTING_FEN, san).unwrap_err();
+            assert!(error.starts_with("Illegal move"), "{}: {}", san, error);
+        }
+        for san in ["", "Z", "Qz9", "Ne4=Q"] {
+            let error = parse(STARTING_FEN, san).unwrap_err();
+            assert!(error.starts_with("Invalid move format"), "{}: {}", san, error);
+        }
+
+        let error = parse("4k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a8").unwrap_err();
+        assert!(error.contains("promotion piece required"), "{}", error);
+        assert_eq!(parse("4k3/P7/8/8/8/8/8/4K3 w - - 0 1", "a8=Q").unwrap(), "a7a8q");
+    }
+
+    /// Chess960 position with the king on b1, rooks on a1 and g1
+    const KING_B1: &str = "rk4r1/pppppppp/8/8/8/8/PPPPPPPP/RK4R1 w GAga - 0 1";
+
+    #[test]
+    fn chess960_castling_is_king_takes_rook() {
+        let kingside = ChessService::make_move(KING_B1, "b1g1").unwrap();
+        assert_eq!(kingside, "rk4r1/pppppppp/8/8/8/8/PPPPPPPP/R4RK1 b ga - 1 1");
+        let queenside = ChessService::make_move(KING_B1, "b1a1").unwrap();
+        assert_eq!(queenside, "rk4r1/pppppppp/8/8/8/8/PPPPPPPP/2KR2R1 b ga - 1 1");
+
+        // SAN castling means the same moves, reported with the king's landing square
+        assert_eq!(parse(KING_B1, "O-O").unwrap(), "b1g1");
+        assert_eq!(parse(KING_B1, "O-O-O").unwrap(), "b1a1");
+        let details = ChessService::describe_move(KING_B1, "b1g1").unwrap();
+        assert_eq!(details.san, "O-O");
+        assert_eq!(details.castling.as_deref(), Some("kingside"));
+        assert_eq!(details.to, "g1");
+        assert_eq!(details.captured, None);
+    }
+
+    #[test]
+    fn chess960_castling_with_the_king_on_its_square() {
+        // The king stays on g1, only the rook moves
+        let fen = "1r4kr/pppppppp/8/8/8/8/PPPPPPPP/1R4KR w HBhb - 0 1";
+        let after = ChessService::make_move(fen, "g1h1").unwrap();
+        assert_eq!(after, "1r4kr/pppppppp/8/8/8/8/PPPPPPPP/1R3RK1 b hb - 1 1");
+    }
+
+    #[test]
+    fn chess960_castling_needs_a_clear_path_and_the_right() {
+        // Bishop on e1, between the king and its destination
+        let blocked = "rk4r1/pppppppp/8/8/8/8/PPPPPPPP/RK2B1R1 w GAga - 0 1";
+        assert!(parse(blocked, "b1g1").unwrap_err().starts_with("Illegal move"));
+        assert!(parse(blocked, "O-O").is_err());
+        assert_eq!(parse(blocked, "O-O-O").unwrap(), "b1a1");
+
+        // Kingside right already lost
+        let no_right = "rk4r1/pppppppp/8/8/8/8/PPPPPPPP/RK4R1 w Aga - 0 1";
+        assert!(parse(no_right, "b1g1").is_err());
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,140 @@
+use chrono::{DateTime, Utc};
+use crate::models::{Game, TimeControlInput};
+
+/// Longest base time accepted (3 hours per side)
+const MAX_BASE_SECONDS: i32 = 3 * 60 * 60;
+
+/// Largest increment accepted (3 minutes per move)
+const MAX_INCREMENT_SECONDS: i32 = 180;
+
+/// Longest correspondence time per move
+const MAX_DAYS_PER_MOVE: i32 = 14;
+
+const MS_PER_DAY: i64 = 24 * 60 * 60 * 1000;
+
+/// Service responsible for the server-side chess clocks of timed games
+pub struct ClockService;
+
+impl ClockService {
+    /// Validates a time control and sets both clocks of a new game to the base time
+    /// 
+    /// # Arguments
+    /// * `game` - New game (modified in place)
+    /// * `time_control` - Base + increment, or correspondence days per move
+    /// 
+    /// # Returns
+    /// Result<(), String> - Error message describing an invalid time control
+    pub fn setup(game: &mut Game, time_control: &TimeControlInput) -> Result<(), String> {
+        let increment = time_control.increment_seconds;
+        let (base_ms, increment_ms, per_move) = match (time_control.base_seconds, time_control.days_per_move) {
+            (Some(_), Some(_)) => return Err("Invalid time control: use either baseSeconds or daysPerMove".to_string()),
+            (None, None) => return Err("Invalid time control: baseSeconds or daysPerMove is required".to_string()),
+            (Some(base), None) => {
+                if !(1..=MAX_BASE_SECONDS).contains(&base) {
+                    return Err(format!("Invalid time control: baseSeconds must be between 1 and {}", MAX_BASE_SECONDS));
+                }
+                if !(0..=MAX_INCREMENT_SECONDS).contains(&increment) {
+                    return Err(format!("Invalid time control: incrementSeconds must be between 0 and {}", MAX_INCREMENT_SECONDS));
+                }
+                (base as i64 * 1000, increment as i64 * 1000, false)
+            },
+            (None, Some(days)) => {
+                if !(1..=MAX_DAYS_PER_MOVE).contains(&days) {
+                    return Err(format!("Invalid time control: daysPerMove must be between 1 and {}", MAX_DAYS_PER_MOVE));
+                }
+                if increment != 0 {
+                    return Err("Invalid time control: incrementSeconds cannot be combined with daysPerMove".to_string());
+                }
+                (days as i64 * MS_PER_DAY, 0, true)
+            },
+        };
+
+        game.clock_base_ms = Some(base_ms);
+        game.clock_increment_ms = increment_ms;
+        game.clock_per_move = per_move;
+        game.white_clock_ms = Some(base_ms);
+        game.black_clock_ms = Some(base_ms);
+        Ok(())
+    }
+
+    /// Remaining time of a side, including the time spent on the move in progress
+    /// 
+    /// # Arguments
+    /// * `game` - Game to read the clocks from
+    /// * `color` - "white" or "black"
+    /// * `now` - Current time
+    /// 
+    /// # Returns
+    /// Option<i64> - Milliseconds left (zero or less once the flag has fallen), None when untimed
+    pub fn remaining_ms(game: &Game, color: &str, now: DateTime<Utc>) -> Option<i64> {
+        let stored = if color == "white" { game.white_clock_ms } else { game.black_clock_ms }?;
+        let elapsed = match game.turn_started_at {
+            Some(started) if Self::side_to_move(game) == color => (now - started).num_milliseconds().max(0),
+            _ => 0,
+        };
+        Some(stored - elapsed)
+    }
+
+    /// Stops the clock of the side that just moved and adds the increment
+    /// 
+    /// # Arguments
+    /// * `game` - Game (modified in place)
+    /// * `color` - Side that moved ("white" or "black")
+    /// * `elapsed_ms` - Time the side spent on the move
+    /// 
+    /// # Returns
+    /// bool - false when the side ran out of time (its clock is left at zero)
+    pub fn press(game: &mut Game, color: &str, elapsed_ms: i64) -> bool {
+        let (base, increment, per_move) = (game.clock_base_ms, game.clock_increment_ms, game.clock_per_move);
+        let clock = if color == "white" { &mut game.white_clock_ms } else { &mut game.black_clock_ms };
+        let Some(remaining) = clock.as_mut() else {
+            return true;
+        };
+
+        let left = *remaining - elapsed_ms;
+        if left <= 0 {
+            *remaining = 0;
+            return false;
+        }
+
+        // Correspondence clocks start again from the full time for the next move
+        *remaining = match (per_move, base) {
+            (true, Some(base)) => base,
+            _ => left + increment,
+        };
+        true
+    }
+
+    /// Human readable time control ("5+3", "30s+0", "2 days per move")
+    /// 
+    /// # Returns
+    /// Option<String> - Label, None for untimed games
+    pub fn label(game: &Game) -> Option<String> {
+        let base = game.clock_base_ms?;
+        if game.clock_per_move {
+            let days = base / MS_PER_DAY;
+            return Some(format!("{} day{} per move", days, if days > 1 { "s" } else { "" }));
+        }
+
+        let increment = game.clock_increment_ms / 1000;
+        if base % 60_000 == 0 {
+            Some(format!("{}+{}", base / 60_000, increment))
+        } else {
+            Some(format!("{}s+{}", base / 1000, increment))
+        }
+    }
+
+    /// PGN TimeControl tag value ("300+3", or "1/86400" for one move per day)
+    pub fn pgn_tag(game: &Game) -> Option<String> {
+        let base = game.clock_base_ms? / 1000;
+        if game.clock_per_move {
+            Some(format!("1/{}", base))
+        } else {
+            Some(format!("{}+{}", base, game.clock_increment_ms / 1000))
+        }
+    }
+
+    fn side_to_move(game: &Game) -> &'static str {
+        if game.fen.split_whitespace().nth(1) == Some("b") { "black" } else { "white" }
+    }
+}
@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, RwLock};
+use chrono::Utc;
+use sqlx::SqlitePool;
+use tracing::{debug, info};
+use crate::database::{delete_difficulty_profile, get_difficulty_profiles, upsert_difficulty_profile};
+use crate::models::{DifficultyProfile, DifficultyProfileInput, Game};
+
+/// Longest thinking time a profile may ask for
+const MAX_MOVETIME_MS: i32 = 10_000;
+
+/// Largest number of candidate moves a profile may ask Stockfish for
+const MAX_MULTIPV: i32 = 50;
+
+/// Difficulty profiles in use, swapped as a whole whenever the table changes
+#[derive(Default)]
+pub struct DifficultyProfiles {
+    profiles: HashMap<String, DifficultyProfile>,
+}
+
+impl DifficultyProfiles {
+    /// Profiles shared by the whole application
+    pub fn global() -> Arc<DifficultyProfiles> {
+        Self::slot()
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    fn slot() -> &'static RwLock<Arc<DifficultyProfiles>> {
+        static PROFILES: OnceLock<RwLock<Arc<DifficultyProfiles>>> = OnceLock::new();
+        PROFILES.get_or_init(|| RwLock::new(Arc::new(DifficultyProfiles::default())))
+    }
+
+    /// Loads the profiles from the database; games started before keep playing with the new ones
+    ///
+    /// # Returns
+    /// Result<usize, String> - Number of profiles loaded or error message
+    pub async fn reload(pool: &SqlitePool) -> Result<usize, String> {
+        let profiles = get_difficulty_profiles(pool)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?;
+        let count = profiles.len();
+        let profiles = profiles.into_iter().map(|profile| (profile.name.clone(), profile)).collect();
+        *Self::slot().write().unwrap_or_else(|e| e.into_inner()) = Arc::new(DifficultyProfiles { profiles });
+        info!("🎚️ {} difficulty profile(s) loaded", count);
+        Ok(count)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&DifficultyProfile> {
+        self.profiles.get(name)
+    }
+
+    /// All profiles, by level then name
+    pub fn all(&self) -> Vec<DifficultyProfile> {
+        let mut profiles: Vec<DifficultyProfile> = self.profiles.values().cloned().collect();
+        profiles.sort_by(|a, b| a.level.cmp(&b.level).then_with(|| a.name.cmp(&b.name)));
+        profiles
+    }
+
+    /// Profile the bot plays a game with: the game's named profile, else its level's
+    ///
+    /// A named profile missing from the table (e.g., deleted while the game was being played)
+    /// falls back to the game's level, and a missing level profile to full strength.
+    pub fn for_game(&self, game: &Game) -> DifficultyProfile {
+        let level_name = game.difficulty.to_string();
+        if let Some(name) = &game.difficulty_profile {
+            if let Some(profile) = self.profiles.get(name) {
+                return profile.clone();
+            }
+            debug!("Difficulty profile {} not found, playing level {}", name, level_name);
+        }
+        match self.profiles.get(&level_name) {
+            Some(profile) => profile.clone(),
+            None => {
+                debug!("Difficulty profile {} not found, playing at full strength", level_name);
+                DifficultyProfile {
+                    name: level_name,
+                    level: game.difficulty,
+                    skill_level: 20,
+                    movetime_ms: 200,
+                    uci_elo: None,
+                    multipv: 1,
+                    random_move_chance: 0.0,
+                    blunder_chance: 0.0,
+                    any_candidate_chance: 0.0,
+                    weak_candidate_chance: 0.0,
+                    near_best_chance: 0.0,
+                    updated_at: Utc::now(),
+                }
+            }
+        }
+    }
+}
+
+/// Service for editing difficulty profiles
+pub struct DifficultyService;
+
+impl DifficultyService {
+    /// Creates or replaces a profile, then reloads the profiles in use
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `input` - Profile settings (checked against their allowed ranges)
+    ///
+    /// # Returns
+    /// Result<DifficultyProfile, String> - Saved profile or validation error
+    pub async fn save_profile(pool: &SqlitePool, input: DifficultyProfileInput) -> Result<DifficultyProfile, String> {
+        Self::validate(&input)?;
+        let profile = DifficultyProfile {
+            name: input.name.trim().to_string(),
+            level: input.level,
+            skill_level: input.skill_level,
+            movetime_ms: input.movetime_ms,
+            uci_elo: input.uci_elo,
+            multipv: input.multipv,
+            random_move_chance: input.random_move_chance,
+            blunder_chance: input.blunder_chance,
+            any_candidate_chance: input.any_candidate_chance,
+            weak_candidate_chance: input.weak_candidate_chance,
+            near_best_chance: input.near_best_chance,
+            updated_at: Utc::now(),
+        };
+        upsert_difficulty_profile(pool, &profile)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?;
+        DifficultyProfiles::reload(pool).await?;
+        Ok(profile)
+    }
+
+    /// Deletes a named profile (the profiles of levels 1 to 20 can only be edited)
+    ///
+    /// # Returns
+    /// Result<bool, String> - Whether the profile existed
+    pub async fn delete_profile(pool: &SqlitePool, name: &str) -> Result<bool, String> {
+        if Self::level_of_name(name).is_some() {
+            return Err(format!("Profile {} backs a difficulty level and cannot be deleted", name));
+        }
+        let deleted = delete_difficulty_profile(pool, name)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?;
+        DifficultyProfiles::reload(pool).await?;
+        Ok(deleted)
+    }
+
+    fn validate(input: &DifficultyProfileInput) -> Result<(), String> {
+        let name = input.name.trim();
+        if name.is_empty() || name.len() > 32 {
+            return Err("Invalid profile name (1 to 32 characters)".to_string());
+        }
+        if !(1..=20).contains(&input.level) {
+            return Err(format!("Invalid level: {} (expected 1 to 20)", input.level));
+        }
+        if Self::level_of_name(name).is_some_and(|level| level != input.level) {
+            return Err(format!("Profile {} backs level {} and must keep that level", name, name));
+        }
+        if !(-20..=20).contains(&input.skill_level) {
+            return Err(format!("Invalid skill level: {} (expected -20 to 20)", input.skill_level));
+        }
+        if !(1..=MAX_MOVETIME_MS).contains(&input.movetime_ms) {
+            return Err(format!("Invalid movetimeMs: {} (expected 1 to {})", input.movetime_ms, MAX_MOVETIME_MS));
+        }
+        if let Some(elo) = input.uci_elo.filter(|elo| !(100..=3500).contains(elo)) {
+            return Err(format!("Invalid uciElo: {} (expected 100 to 3500)", elo));
+        }
+        if !(1..=MAX_MULTIPV).contains(&input.multipv) {
+            return Err(format!("Invalid multipv: {} (expected 1 to {})", input.multipv, MAX_MULTIPV));
+        }
+        let chances = [
+            ("randomMoveChance", input.random_move_chance),
+            ("blunderChance", input.blunder_chance),
+            ("anyCandidateChance", input.any_candidate_chance),
+            ("weakCandidateChance", input.weak_candidate_chance),
+            ("nearBestChance", input.near_best_chance),
+        ];
+        for (field, chance) in chances {
+            if !(0.0..=1.0).contains(&chance) {
+                return Err(format!("Invalid {}: {} (expected 0 to 1)", field, chance));
+            }
+        }
+        Ok(())
+    }
+
+    /// Level backed by a profile name ("1" to "20")
+    fn level_of_name(name: &str) -> Option<i32> {
+        name.parse().ok().filter(|level| (1..=20).contains(level))
+    }
+}
@@ -0,0 +1,327 @@
+use std::ops::{Deref, DerefMut};
+use std::process::Stdio;
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader as TokioBufReader};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command as TokioCommand};
+use tokio::sync::{Mutex, OwnedSemaphorePermit, Semaphore};
+use std::sync::Mutex as StdMutex;
+use tokio::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+use chess::{Board, Color, MoveGen, ALL_PIECES};
+use crate::services::{ChessService, StockfishService};
+
+/// Number of engine processes when STOCKFISH_POOL_SIZE is not set
+const DEFAULT_POOL_SIZE: usize = 2;
+
+/// Time a new process gets to answer the `uci` handshake
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Time a process gets to answer `isready` before it is considered dead
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// A running UCI engine process, already past the `uci` handshake
+pub struct EngineProcess {
+    // Killed when the process is dropped
+    _child: Child,
+    pub stdin: ChildStdin,
+    pub reader: TokioBufReader<ChildStdout>,
+    /// Position of the last search, to tell whether the next one is still the same game
+    last_fen: Option<String>,
+}
+
+impl EngineProcess {
+    /// Starts an engine and waits for `uciok` and `readyok`
+    async fn spawn(command: &str) -> Result<EngineProcess, String> {
+        let mut child = TokioCommand::new(command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| format!("Failed to start Stockfish: {}", e))?;
+        let stdin = child.stdin.take().ok_or("Failed to open stdin")?;
+        let stdout = child.stdout.take().ok_or("Failed to open stdout")?;
+
+        let mut process = EngineProcess { _child: child, stdin, reader: TokioBufReader::new(stdout), last_fen: None };
+        process.send("uci").await?;
+        process.wait_for("uciok", HANDSHAKE_TIMEOUT).await?;
+        process.send("isready").await?;
+        process.wait_for("readyok", HANDSHAKE_TIMEOUT).await?;
+        Ok(process)
+    }
+
+    /// Sends one command line to the engine
+    pub async fn send(&mut self, command: &str) -> Result<(), String> {
+        self.stdin
+            .write_all(format!("{}\n", command).as_bytes())
+            .await
+            .map_err(|e| format!("Failed to write to Stockfish: {}", e))
+    }
+
+    /// Reads and discards output until a line starting with `token`
+    async fn wait_for(&mut self, token: &str, limit: Duration) -> Result<(), String> {
+        let start = Instant::now();
+        let mut line = String::new();
+        loop {
+            let remaining = limit.checked_sub(start.elapsed()).unwrap_or(Duration::from_millis(0));
+            match tokio::time::timeout(remaining, self.reader.read_line(&mut line)).await {
+                Ok(Ok(n)) if n > 0 => {
+                    if line.starts_with(token) {
+                        return Ok(());
+                    }
+                    line.clear();
+                }
+                Ok(Ok(_)) => return Err("Stockfish exited".to_string()),
+                Ok(Err(e)) => return Err(format!("Failed to read from Stockfish: {}", e)),
+                Err(_) => return Err(format!("Stockfish did not answer {}", token)),
+            }
+        }
+    }
+
+    /// Reads output until `bestmove`, asking the engine to stop once `limit` has passed
+    ///
+    /// # Returns
+    /// Result<Option<String>, String> - Best move, None if the engine had no move or never answered
+    pub async fn read_bestmove(&mut self, limit: Duration) -> Result<Option<String>, String> {
+        let start = Instant::now();
+        let mut max = limit;
+        let mut stopped = false;
+        let mut line = String::new();
+        loop {
+            let remaining = max.checked_sub(start.elapsed()).unwrap_or(Duration::from_millis(0));
+            if remaining.is_zero() {
+                if stopped {
+                    warn!("Engine read timeout reached");
+                    return Ok(None);
+                }
+                self.send("stop").await?;
+                stopped = true;
+                max += HEALTH_CHECK_TIMEOUT;
+                continue;
+            }
+            match tokio::time::timeout(remaining, self.reader.read_line(&mut line)).await {
+                Ok(Ok(n)) if n > 0 => {
+                    if line.starts_with("bestmove") {
+                        return Ok(line.split_whitespace().nth(1).filter(|m| *m != "(none)").map(|m| m.to_string()));
+                    }
+                    line.clear();
+                }
+                Ok(Ok(_)) => return Err("Engine exited".to_string()),
+                Ok(Err(e)) => return Err(format!("Failed to read from engine: {}", e)),
+                Err(_) => continue,
+            }
+        }
+    }
+
+    /// Stops any search in progress and checks the engine still answers
+    ///
+    /// Output left over from the previous search (e.g., a late `bestmove`) is discarded.
+    async fn sync(&mut self) -> Result<(), String> {
+        self.send("stop").await?;
+        self.send("isready").await?;
+        self.wait_for("readyok", HEALTH_CHECK_TIMEOUT).await
+    }
+}
+
+/// An engine process lent by the pool
+///
+/// Give it back with `release` once the search is over; a process dropped without being
+/// released (e.g., after an error) is killed and replaced by a fresh one later.
+pub struct PooledEngine {
+    process: Option<EngineProcess>,
+    pool: &'static EnginePool,
+    _permit: OwnedSemaphorePermit,
+}
+
+impl PooledEngine {
+    /// Returns the process to the pool if it is still healthy
+    pub async fn release(mut self) {
+        if let Some(mut process) = self.process.take() {
+            match process.sync().await {
+                Ok(()) => self.pool.idle.lock().await.push(process),
+                Err(e) => warn!("Discarding Stockfish process: {}", e),
+            }
+        }
+    }
+}
+
+impl Deref for PooledEngine {
+    type Target = EngineProcess;
+
+    fn deref(&self) -> &EngineProcess {
+        self.process.as_ref().expect("engine already released")
+    }
+}
+
+impl DerefMut for PooledEngine {
+    fn deref_mut(&mut self) -> &mut EngineProcess {
+        self.process.as_mut().expect("engine already released")
+    }
+}
+
+/// Long-lived engine processes shared by every search with the same engine binary
+///
+/// At most `size` processes run at once; extra searches wait for a free one. Processes are
+/// started on demand, health-checked before every use and restarted when they crashed.
+pub struct EnginePool {
+    command: String,
+    size: usize,
+    idle: Mutex<Vec<EngineProcess>>,
+    permits: Arc<Semaphore>,
+}
+
+impl EnginePool {
+    /// Stockfish pool shared by the whole application, sized by STOCKFISH_POOL_SIZE
+    pub fn global() -> &'static EnginePool {
+        static POOL: OnceLock<EnginePool> = OnceLock::new();
+        POOL.get_or_init(|| {
+            let pool = EnginePool::new(StockfishService::get_stockfish_command(), Self::size_from_env());
+            info!("♟️ Stockfish pool ready ({} process(es) max)", pool.size);
+            pool
+        })
+    }
+
+    /// Pool of another UCI engine binary, created on first use (same size as the Stockfish pool)
+    pub fn for_command(command: &str) -> &'static EnginePool {
+        static POOLS: OnceLock<StdMutex<HashMap<String, &'static EnginePool>>> = OnceLock::new();
+        let mut pools = POOLS
+            .get_or_init(|| StdMutex::new(HashMap::new()))
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+        pools.entry(command.to_string()).or_insert_with(|| {
+            info!("♟️ Engine pool ready for {}", command);
+            // Pools live as long as the application
+            Box::leak(Box::new(EnginePool::new(command.to_string(), Self::size_from_env())))
+        })
+    }
+
+    fn size_from_env() -> usize {
+        std::env::var("STOCKFISH_POOL_SIZE")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .filter(|size| *size > 0)
+            .unwrap_or(DEFAULT_POOL_SIZE)
+    }
+
+    fn new(command: String, size: usize) -> EnginePool {
+        EnginePool {
+            command,
+            size,
+            idle: Mutex::new(Vec::new()),
+            permits: Arc::new(Semaphore::new(size)),
+        }
+    }
+
+    /// Borrows a healthy engine for a search, reset with `ucinewgame` unless it last searched
+    /// the same game
+    ///
+    /// # Arguments
+    /// * `fen` - Position about to be searched
+    ///
+    /// # Returns
+    /// Result<PooledEngine, String> - Engine ready for the search or error message
+    pub async fn checkout(&'static self, fen: &str) -> Result<PooledEngine, String> {
+        let permit = self
+            .permits
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(|e| format!("Stockfish pool closed: {}", e))?;
+        let board = ChessService::load_board(fen).ok();
+
+        loop {
+            let idle = {
+                let mut idle = self.idle.lock().await;
+                // A process that searched this game last keeps what it learned
+                let same_game = idle
+                    .iter()
+                    .position(|process| Self::continues(process.last_fen.as_deref(), board.as_ref()));
+                match same_game {
+                    Some(index) => Some((idle.swap_remove(index), false)),
+                    None => idle.pop().map(|process| (process, true)),
+                }
+            };
+            let Some((mut process, new_game)) = idle else { break };
+            match Self::prepare(&mut process, new_game).await {
+                Ok(()) => {
+                    process.last_fen = Some(fen.to_string());
+                    return Ok(PooledEngine { process: Some(process), pool: self, _permit: permit });
+                }
+                Err(e) => warn!("Restarting Stockfish process: {}", e),
+            }
+        }
+
+        debug!("Starting Stockfish process: {}", self.command);
+        let mut process = EngineProcess::spawn(&self.command).await?;
+        process.last_fen = Some(fen.to_string());
+        Ok(PooledEngine { process: Some(process), pool: self, _permit: permit })
+    }
+
+    /// Health check of an idle process (`isready`), which first clears what it learned from
+    /// its last search when it starts another game
+    async fn prepare(process: &mut EngineProcess, new_game: bool) -> Result<(), String> {
+        if new_game {
+            process.send("ucinewgame").await?;
+        }
+        process.send("isready").await?;
+        process.wait_for("readyok", HEALTH_CHECK_TIMEOUT).await
+    }
+
+    /// Whether a position follows the last one searched: the same position, or at most two
+    /// plies later (the engine's move and the reply)
+    fn continues(last_fen: Option<&str>, board: Option<&Board>) -> bool {
+        let (Some(last_fen), Some(board)) = (last_fen, board) else {
+            return false;
+        };
+        let Ok(last) = ChessService::load_board(last_fen) else {
+            return false;
+        };
+        if Self::same_position(&last, board) {
+            return true;
+        }
+        ChessService::legal_moves(&last, last_fen)
+            .into_iter()
+            .filter_map(|chess_move| ChessService::play(&last, chess_move).ok())
+            .any(|after| {
+                Self::same_position(&after, board)
+                    || MoveGen::new_legal(&after).any(|reply| Self::same_position(&after.make_move_new(reply), board))
+            })
+    }
+
+    /// Same pieces on the same squares and same side to move (castling and en passant
+    /// rights aside)
+    fn same_position(a: &Board, b: &Board) -> bool {
+        a.side_to_move() == b.side_to_move()
+            && a.color_combined(Color::White) == b.color_combined(Color::White)
+            && ALL_PIECES.iter().all(|piece| a.pieces(*piece) == b.pieces(*piece))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AFTER_E4: &str = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1";
+
+    fn continues(last_fen: Option<&str>, fen: &str) -> bool {
+        EnginePool::continues(last_fen, ChessService::load_board(fen).ok().as_ref())
+    }
+
+    #[test]
+    fn same_game_within_two_plies() {
+        let after_e5 = "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq - 0 2";
+        let after_nf3 = "rnbqkbnr/pppp1ppp/8/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 2";
+        assert!(continues(Some(AFTER_E4), AFTER_E4));
+        assert!(continues(Some(AFTER_E4), after_e5));
+        assert!(continues(Some(AFTER_E4), after_nf3));
+    }
+
+    #[test]
+    fn other_positions_start_a_new_game() {
+        let after_d4 = "rnbqkbnr/pppppppp/8/8/3P4/8/PPP1PPPP/RNBQKBNR b KQkq - 0 1";
+        assert!(!continues(None, AFTER_E4));
+        assert!(!continues(Some(AFTER_E4), after_d4));
+        assert!(!continues(Some("not a fen"), AFTER_E4));
+    }
+}
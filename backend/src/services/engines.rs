@@ -0,0 +1,308 @@
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, RwLock};
+use async_trait::async_trait;
+use serde::Deserialize;
+use tokio::time::Duration;
+use tracing::{debug, info, warn};
+use crate::models::{DifficultyProfile, Game, Personality};
+use crate::services::{ChessService, StockfishService};
+use crate::services::builtin_search::BuiltinSearch;
+use crate::services::engine_pool::EnginePool;
+
+/// Name of the engine used when neither the level nor the bot picks another one
+pub const DEFAULT_ENGINE: &str = "stockfish";
+
+/// Name of the engine that runs inside the server, without any external binary
+pub const BUILTIN_ENGINE: &str = "builtin";
+
+/// Thinking time of a generic UCI engine when its configuration doesn't set one
+const DEFAULT_UCI_MOVETIME_MS: u32 = 500;
+
+/// Largest game seed: seeds stay exact in JavaScript numbers
+pub const MAX_SEED: i64 = (1 << 53) - 1;
+
+/// Seed of the bot's random choices for one move of a game
+///
+/// Each move gets its own seed, so replaying a game from any position makes the same random
+/// choices whatever happened before (takebacks, restarts...). The move itself also depends on
+/// what the engine found in its time (see `BuiltinSearch::best_move`).
+///
+/// # Arguments
+/// * `game_seed` - Seed stored on the game
+/// * `ply` - Number of half-moves played before the move
+pub fn move_seed(game_seed: i64, ply: i32) -> u64 {
+    (game_seed as u64).wrapping_add((ply as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15))
+}
+
+/// Something that chooses the bot's moves
+#[async_trait]
+pub trait ChessEngine: Send + Sync {
+    /// Name shown to players (e.g., in PGN exports)
+    fn display_name(&self) -> String;
+
+    /// Chooses a move for the side to move
+    ///
+    /// # Arguments
+    /// * `fen` - Current position (Shredder-FEN castling for Chess960)
+    /// * `profile` - Difficulty profile of the game (engines may ignore it)
+    /// * `personality` - Style of the game's bot (engines may ignore it)
+    /// * `seed` - Seed of any random choice (see `move_seed`)
+    ///
+    /// # Returns
+    /// Result<String, String> - Move in coordinate notation or error message
+    async fn best_move(&self, fen: &str, profile: &DifficultyProfile, personality: Personality, seed: u64) -> Result<String, String>;
+}
+
+/// Stockfish weakened by the difficulty profile (see `StockfishService::get_best_move`)
+///
+/// When Stockfish can't be started or fails mid-search, the built-in engine plays the move
+/// so games stay playable without any external binary.
+pub struct StockfishEngine;
+
+#[async_trait]
+impl ChessEngine for StockfishEngine {
+    fn display_name(&self) -> String {
+        "Stockfish".to_string()
+    }
+
+    async fn best_move(&self, fen: &str, profile: &DifficultyProfile, personality: Personality, seed: u64) -> Result<String, String> {
+        match StockfishService::get_best_move(fen, profile, personality, seed).await {
+            Ok(best_move) => Ok(best_move),
+            Err(e) => {
+                warn!("Stockfish unavailable ({}), playing with the built-in engine", e);
+                BuiltinEngine.best_move(fen, profile, personality, seed).await
+            }
+        }
+    }
+}
+
+/// Any UCI engine (Leela, Ethereal, Komodo...) configured by path and options
+///
+/// The engine plays at the strength and in the style its options give it: the game's
+/// difficulty only picks which configuration is used.
+pub struct UciEngine {
+    name: String,
+    path: String,
+    options: HashMap<String, String>,
+    movetime_ms: u32,
+}
+
+#[async_trait]
+impl ChessEngine for UciEngine {
+    fn display_name(&self) -> String {
+        self.name.clone()
+    }
+
+    async fn best_move(&self, fen: &str, _profile: &DifficultyProfile, _personality: Personality, _seed: u64) -> Result<String, String> {
+        let mut engine = EnginePool::for_command(&self.path).checkout(fen).await?;
+
+        // Pooled processes keep their options: every option is set for each search
+        for (name, value) in &self.options {
+            engine.send(&format!("setoption name {} value {}", name, value)).await?;
+        }
+        engine
+            .send(&format!("setoption name UCI_Chess960 value {}", ChessService::is_chess960_fen(fen)))
+            .await?;
+        engine.send("isready").await?;
+        engine.send(&format!("position fen {}", fen)).await?;
+        engine.send(&format!("go movetime {}", self.movetime_ms)).await?;
+
+        let best_move = engine
+            .read_bestmove(Duration::from_millis(self.movetime_ms as u64 + 1000))
+            .await?;
+        engine.release().await;
+
+        best_move.ok_or_else(|| format!("{} returned no move", self.name))
+    }
+}
+
+/// Alpha-beta engine running inside the server (see `BuiltinSearch`)
+///
+/// Strong enough for low and mid levels; higher levels get its full strength.
+pub struct BuiltinEngine;
+
+#[async_trait]
+impl ChessEngine for BuiltinEngine {
+    fn display_name(&self) -> String {
+        "Built-in engine".to_string()
+    }
+
+    async fn best_move(&self, fen: &str, profile: &DifficultyProfile, personality: Personality, seed: u64) -> Result<String, String> {
+        let fen = fen.to_string();
+        let limits = BuiltinSearch::limits_for_level(profile.level);
+        // The search is CPU-bound: keep it off the async workers
+        tokio::task::spawn_blocking(move || BuiltinSearch::best_move(&fen, &limits, personality, seed))
+            .await
+            .map_err(|e| format!("Built-in engine failed: {}", e))?
+    }
+}
+
+/// Engine configuration file (path in ENGINES_CONFIG), e.g.:
+///
+/// ```json
+/// {
+///   "engines": { "leela": { "path": "/usr/bin/lc0", "options": { "Threads": "2" }, "movetimeMs": 800 } },
+///   "levels": { "1": "builtin", "20": "leela" },
+///   "bots": { "Leela": "leela" }
+/// }
+/// ```
+#[derive(Debug, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct EnginesConfig {
+    #[serde(default)]
+    engines: HashMap<String, UciEngineConfig>,
+    /// Engine name per difficulty level (DEFAULT_ENGINE for levels not listed)
+    #[serde(default)]
+    levels: HashMap<String, String>,
+    /// Engine name per bot that players can pick when creating a game
+    #[serde(default)]
+    bots: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UciEngineConfig {
+    /// Name shown to players (the engine's key when omitted)
+    display_name: Option<String>,
+    path: String,
+    #[serde(default)]
+    options: HashMap<String, String>,
+    movetime_ms: Option<u32>,
+}
+
+/// Engines the server can play with, and which one each level or bot uses
+pub struct EngineRegistry {
+    engines: HashMap<String, Arc<dyn ChessEngine>>,
+    levels: HashMap<i32, String>,
+    bots: HashMap<String, String>,
+}
+
+impl Default for EngineRegistry {
+    fn default() -> Self {
+        let mut engines: HashMap<String, Arc<dyn ChessEngine>> = HashMap::new();
+        engines.insert(DEFAULT_ENGINE.to_string(), Arc::new(StockfishEngine));
+        engines.insert(BUILTIN_ENGINE.to_string(), Arc::new(BuiltinEngine));
+        EngineRegistry { engines, levels: HashMap::new(), bots: HashMap::new() }
+    }
+}
+
+impl EngineRegistry {
+    /// Registry shared by the whole application
+    pub fn global() -> Arc<EngineRegistry> {
+        Self::slot()
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    fn slot() -> &'static RwLock<Arc<EngineRegistry>> {
+        static REGISTRY: OnceLock<RwLock<Arc<EngineRegistry>>> = OnceLock::new();
+        REGISTRY.get_or_init(|| RwLock::new(Arc::new(EngineRegistry::default())))
+    }
+
+    /// Loads the engine configuration file named by ENGINES_CONFIG, if any
+    ///
+    /// # Returns
+    /// Result<(), String> - Error message when the file can't be read or is invalid
+    pub fn load_from_env() -> Result<(), String> {
+        let Ok(path) = std::env::var("ENGINES_CONFIG") else {
+            return Ok(());
+        };
+        let text = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Cannot read engine configuration {}: {}", path, e))?;
+        let registry = Self::from_json(&text)?;
+        info!(
+            "♟️ Engine configuration loaded from {} ({} engines, {} bots)",
+            path,
+            registry.engines.len(),
+            registry.bots.len()
+        );
+        *Self::slot().write().unwrap_or_else(|e| e.into_inner()) = Arc::new(registry);
+        Ok(())
+    }
+
+    /// Builds a registry from the JSON configuration (built-in engines are always available)
+    fn from_json(text: &str) -> Result<EngineRegistry, String> {
+        let config: EnginesConfig =
+            serde_json::from_str(text).map_err(|e| format!("Invalid engine configuration: {}", e))?;
+
+        let mut registry = EngineRegistry::default();
+        for (name, engine) in config.engines {
+            if registry.engines.contains_key(&name) {
+                return Err(format!("Invalid engine configuration: {} is a reserved engine name", name));
+            }
+            let uci = UciEngine {
+                name: engine.display_name.unwrap_or_else(|| name.clone()),
+                path: engine.path,
+                options: engine.options,
+                movetime_ms: engine.movetime_ms.unwrap_or(DEFAULT_UCI_MOVETIME_MS),
+            };
+            registry.engines.insert(name, Arc::new(uci));
+        }
+
+        for (level, engine) in config.levels {
+            let level: i32 = level
+                .parse()
+                .ok()
+                .filter(|level| (1..=20).contains(level))
+                .ok_or_else(|| format!("Invalid engine configuration: level {} (expected 1 to 20)", level))?;
+            registry.check_engine(&engine)?;
+            registry.levels.insert(level, engine);
+        }
+        for (bot, engine) in config.bots {
+            registry.check_engine(&engine)?;
+            registry.bots.insert(bot, engine);
+        }
+        Ok(registry)
+    }
+
+    fn check_engine(&self, engine: &str) -> Result<(), String> {
+        if self.engines.contains_key(engine) {
+            Ok(())
+        } else {
+            Err(format!("Invalid engine configuration: unknown engine {}", engine))
+        }
+    }
+
+    /// Picks the engine of a new game: the bot's engine if a bot was chosen, else the level's
+    ///
+    /// # Returns
+    /// Result<String, String> - Engine name to store on the game, error for an unknown bot
+    pub fn engine_for_new_game(&self, difficulty: i32, bot: Option<&str>) -> Result<String, String> {
+        match bot {
+            Some(bot) => self
+                .bots
+                .get(bot)
+                .cloned()
+                .ok_or_else(|| format!("Unknown bot: {}", bot)),
+            None => Ok(self
+                .levels
+                .get(&difficulty)
+                .cloned()
+                .unwrap_or_else(|| DEFAULT_ENGINE.to_string())),
+        }
+    }
+
+    /// Engine playing a game (Stockfish if the game's engine is no longer configured)
+    pub fn engine_for_game(&self, game: &Game) -> Arc<dyn ChessEngine> {
+        self.engine(&game.engine)
+    }
+
+    /// Engine registered under a name (Stockfish if it is not configured)
+    pub fn engine(&self, name: &str) -> Arc<dyn ChessEngine> {
+        match self.engines.get(name) {
+            Some(engine) => engine.clone(),
+            None => {
+                debug!("Engine {} not configured, using {}", name, DEFAULT_ENGINE);
+                Arc::new(StockfishEngine)
+            }
+        }
+    }
+
+    /// Names of the bots players can pick, sorted
+    pub fn bot_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.bots.keys().cloned().collect();
+        names.sort();
+        names
+    }
+}
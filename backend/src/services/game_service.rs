@@ -1,8 +1,31 @@
-use crate::models::{Game, GameMoveResult, NewGameInput, MakeMoveInput};
-use crate::services::{ChessService, StockfishService, StatsService};
+use crate::models::{Game, GameMove, GameMoveResult, MoveDetails, Termination, Personality, AnalysisStatus, DrawOfferResult, Hint, NewGameInput, ImportPgnInput, MakeMoveInput};
+use crate::services::{ChessService, ClockService, DifficultyProfiles, EngineRegistry, StockfishService, StatsService, PgnService};
+use crate::services::engines::{move_seed, ChessEngine, DEFAULT_ENGINE, MAX_SEED};
+use crate::services::pgn_service::PgnGame;
+use crate::services::chess_service::STARTING_FEN;
+use crate::services::builtin_search::BuiltinSearch;
+use crate::services::stockfish_service::{EngineAnalysis, EngineScore};
+use crate::database::{
+    delete_game_moves_from, get_active_timed_games, get_game_by_id, get_game_moves, get_games_by_user, insert_game,
+    insert_game_move, update_game, update_game_from_ply,
+};
 use uuid::Uuid;
-use chrono::{DateTime, Utc};
+use chrono::Utc;
 use sqlx::SqlitePool;
+use rand::Rng;
+
+/// A game can be aborted (no result, no stats) until this many plies have been played
+const ABORT_MAX_PLIES: i32 = 2;
+
+/// Search depth used by the engine to judge a draw offer
+const DRAW_OFFER_DEPTH: i32 = 12;
+
+/// Search limits of a hint (full strength, whatever the game's level)
+const HINT_DEPTH: i32 = 18;
+const HINT_MOVETIME_MS: i32 = 2000;
+
+/// Error returned when a game was moved, taken back or ended while a request was working on it
+const STALE_GAME_ERROR: &str = "The game changed meanwhile, try again";
 
 /// Service responsible for managing chess games
 pub struct GameService;
@@ -12,16 +35,146 @@ impl GameService {
     /// 
     /// # Arguments
     /// * `pool` - Database connection pool
-    /// * `input` - Game creation parameters (user_id, difficulty)
+    /// * `input` - Game creation parameters (user_id, difficulty or named profile, color,
+    ///   variant, optional start_fen or Chess960 position number, optional time control)
     /// 
     /// # Returns
-    /// A new Game instance initialized with starting position
+    /// A new Game instance initialized with the starting position; when it is not the
+    /// player's turn in that position Stockfish has already played its first move
     pub async fn create_game(pool: &SqlitePool, input: NewGameInput) -> Result<Game, String> {
-        let game = Game {
+        let player_color = Self::resolve_player_color(input.color.as_deref())?;
+        let variant = Self::resolve_variant(input.variant.as_deref())?;
+        let start_fen = Self::resolve_start_fen(&variant, input.start_fen.as_deref(), input.chess960_position)?;
+        let engines = EngineRegistry::global();
+        // A named profile plays at its own level
+        let difficulty = match input.difficulty_profile.as_deref() {
+            Some(name) => DifficultyProfiles::global()
+                .get(name)
+                .map(|profile| profile.level)
+                .ok_or_else(|| format!("Unknown difficulty profile: {}", name))?,
+            None => input.difficulty,
+        };
+        let mut game = Self::new_game(&input.user_id, difficulty, start_fen, player_color, variant);
+        game.difficulty_profile = input.difficulty_profile.clone();
+        if let Some(seed) = input.seed {
+            if !(0..=MAX_SEED).contains(&seed) {
+                return Err(format!("Invalid seed: {} (expected 0 to {})", seed, MAX_SEED));
+            }
+            game.rng_seed = seed;
+        }
+        game.personality = input.personality.unwrap_or_default();
+        game.engine = engines.engine_for_new_game(difficulty, input.bot.as_deref())?;
+        game.takebacks_allowed = input.allow_takebacks;
+        if let Some(time_control) = &input.time_control {
+            ClockService::setup(&mut game, time_control)?;
+        }
+
+        // The engine moves first when it has the white pieces (its clock doesn't run yet)
+        let mut opening_move = None;
+        if !Self::is_player_turn(&game) {
+            let engine = engines.engine_for_game(&game);
+            let (_, game_move) = Self::play_engine_move(&mut game, engine.as_ref()).await?;
+            opening_move = Some(game_move);
+
+            // From a custom position that first move may already end the game
+            let (game_over, winner, draw_reason) = Self::record_position(&mut game);
+            if game_over {
+                let termination = Termination::from_draw_reason(draw_reason.as_deref());
+                Self::finish_game(&mut game, winner.clone(), termination);
+                println!("🏁 Game finished after Stockfish's first move! Winner: {:?}", winner);
+            }
+        }
+        game.turn_started_at = Some(Utc::now());
+
+        let mut tx = pool.begin().await.map_err(|e| format!("Database error: {}", e))?;
+        insert_game(&mut *tx, &game)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?;
+        if let Some(game_move) = &opening_move {
+            insert_game_move(&mut *tx, game_move)
+                .await
+                .map_err(|e| format!("Database error: {}", e))?;
+        }
+        tx.commit().await.map_err(|e| format!("Database error: {}", e))?;
+        Self::record_finished_game(pool, &game).await?;
+
+        println!(
+            "🎯 New game created: {} (Level {}, player {}, {}, engine {}, {})",
+            game.id, game.difficulty, game.player_color, game.variant, game.engine, game.personality.as_str()
+        );
+        Ok(game)
+    }
+
+    /// Validates the colour requested for a new game ("white", "black" or "random")
+    /// 
+    /// # Returns
+    /// Result<String, String> - "white" or "black" (White when no colour was requested)
+    fn resolve_player_color(color: Option<&str>) -> Result<String, String> {
+        match color.map(|c| c.to_lowercase()).as_deref() {
+            None | Some("white") => Ok("white".to_string()),
+            Some("black") => Ok("black".to_string()),
+            Some("random") => {
+                let white = rand::thread_rng().gen_bool(0.5);
+                Ok(if white { "white" } else { "black" }.to_string())
+            },
+            Some(other) => Err(format!("Invalid color: {} (expected white, black or random)", other)),
+        }
+    }
+
+    /// Validates the rules variant requested for a new game ("standard" or "chess960")
+    /// 
+    /// # Returns
+    /// Result<String, String> - Variant name (standard when no variant was requested)
+    fn resolve_variant(variant: Option<&str>) -> Result<String, String> {
+        match variant.map(|v| v.to_lowercase()).as_deref() {
+            None | Some("standard") => Ok("standard".to_string()),
+            Some("chess960") => Ok("chess960".to_string()),
+            Some(other) => Err(format!("Invalid variant: {} (expected standard or chess960)", other)),
+        }
+    }
+
+    /// Picks the starting position of a new game
+    /// 
+    /// Chess960 games start from the requested (or a random) numbered position unless a
+    /// custom FEN is given; standard games start from the custom FEN if any.
+    /// 
+    /// # Returns
+    /// Result<Option<String>, String> - Validated starting FEN, None for the standard position
+    fn resolve_start_fen(
+        variant: &str,
+        start_fen: Option<&str>,
+        chess960_position: Option<i32>,
+    ) -> Result<Option<String>, String> {
+        let chess960 = variant == "chess960";
+        match (start_fen, chess960_position) {
+            (Some(_), Some(_)) => Err("startFen and chess960Position cannot be used together".to_string()),
+            (Some(fen), None) => ChessService::validate_fen(fen, chess960).map(Some),
+            (None, Some(_)) if !chess960 => Err("chess960Position requires the chess960 variant".to_string()),
+            (None, position) if chess960 => {
+                let index = match position {
+                    Some(index) => u32::try_from(index)
+                        .map_err(|_| format!("Invalid Chess960 position: {} (expected 0 to 959)", index))?,
+                    None => rand::thread_rng().gen_range(0..960),
+                };
+                ChessService::chess960_position(index).map(Some)
+            },
+            (None, _) => Ok(None),
+        }
+    }
+
+    /// Builds an active game, not yet stored
+    /// 
+    /// # Arguments
+    /// * `start_fen` - Validated custom starting position, None for the standard position
+    /// * `variant` - "standard" or "chess960"
+    pub(crate) fn new_game(user_id: &str, difficulty: i32, start_fen: Option<String>, player_color: String, variant: String) -> Game {
+        let fen = start_fen.clone().unwrap_or_else(|| STARTING_FEN.to_string());
+        Game {
             id: Uuid::new_v4().to_string(),
-            user_id: input.user_id,
-            difficulty: input.difficulty,
-            fen: "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1".to_string(),
+            user_id: user_id.to_string(),
+            difficulty,
+            position_history: ChessService::position_key(&fen).unwrap_or_default(),
+            fen,
             status: "active".to_string(),
             result: None,
             created_at: Utc::now(),
@@ -29,29 +182,193 @@ impl GameService {
             end_time: None,
             duration_seconds: None,
             moves_count: 0,
+            draw_reason: None,
+            player_color,
+            start_fen,
+            variant,
+            termination: None,
+            takebacks: 0,
+            takebacks_allowed: true,
+            hints_used: 0,
+            analysis_status: None,
+            engine: DEFAULT_ENGINE.to_string(),
+            difficulty_profile: None,
+            rng_seed: rand::thread_rng().gen_range(0..=MAX_SEED),
+            personality: Personality::Balanced,
+            clock_base_ms: None,
+            clock_increment_ms: 0,
+            clock_per_move: false,
+            white_clock_ms: None,
+            black_clock_ms: None,
+            turn_started_at: None,
+        }
+    }
+
+    /// Returns true when the side to move in the game's position is the player's
+    fn is_player_turn(game: &Game) -> bool {
+        let side_to_move = if game.fen.split_whitespace().nth(1) == Some("b") { "black" } else { "white" };
+        side_to_move == game.player_color
+    }
+
+    /// Side played by the engine
+    fn engine_color(game: &Game) -> &'static str {
+        if game.player_color == "white" { "black" } else { "white" }
+    }
+
+    /// Imports games from a PGN file
+    /// 
+    /// Every game is replayed move by move through ChessService before anything is stored;
+    /// one invalid game rejects the whole import.
+    /// 
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `input` - PGN text, owner, difficulty and whether to count finished games in stats
+    /// 
+    /// # Returns
+    /// Imported games, finished ones with their result and unfinished ones `active` so they
+    /// can be continued against Stockfish from the last position (Stockfish has already
+    /// replied when the game stopped on its turn)
+    pub async fn import_pgn(pool: &SqlitePool, input: ImportPgnInput) -> Result<Vec<Game>, String> {
+        let pgn_games = PgnService::parse_pgn(&input.pgn)?;
+        let player_color = Self::resolve_player_color(input.color.as_deref())?;
+
+        let mut replayed = Vec::new();
+        for (index, pgn_game) in pgn_games.iter().enumerate() {
+            let (game, moves) = Self::replay_pgn_game(&input.user_id, input.difficulty, &player_color, pgn_game)
+                .map_err(|e| format!("Game {}: {}", index + 1, e))?;
+            replayed.push((game, moves));
+        }
+
+        // An unfinished game stopped on the engine's turn: the engine replies before it is stored
+        for (index, (game, moves)) in replayed.iter_mut().enumerate() {
+            if game.status != "active" || Self::is_player_turn(game) {
+                continue;
+            }
+            let engine = EngineRegistry::global().engine_for_game(game);
+            let (_, game_move) = Self::play_engine_move(game, engine.as_ref())
+                .await
+                .map_err(|e| format!("Game {}: {}", index + 1, e))?;
+            moves.push(game_move);
+
+            let (game_over, winner, draw_reason) = Self::record_position(game);
+            if game_over {
+                let termination = Termination::from_draw_reason(draw_reason.as_deref());
+                Self::finish_imported_game(game, winner, termination, draw_reason);
+            }
+        }
+
+        // All the games or none
+        let mut tx = pool.begin().await.map_err(|e| format!("Database error: {}", e))?;
+        for (game, moves) in &replayed {
+            insert_game(&mut *tx, game)
+                .await
+                .map_err(|e| format!("Database error: {}", e))?;
+            for game_move in moves {
+                insert_game_move(&mut *tx, game_move)
+                    .await
+                    .map_err(|e| format!("Database error: {}", e))?;
+            }
+        }
+        tx.commit().await.map_err(|e| format!("Database error: {}", e))?;
+
+        let mut games = Vec::new();
+        for (game, _) in replayed {
+            if input.count_in_stats && game.status == "finished" {
+                Self::update_stats(pool, &game, game.duration_seconds.unwrap_or(0), false).await?;
+            }
+
+            println!("📥 Imported game {} ({} moves, {})", game.id, game.moves_count, game.status);
+            games.push(game);
+        }
+
+        Ok(games)
+    }
+
+    /// Replays a parsed PGN game from the starting position, validating every move
+    ///
+    /// # Returns
+    /// The game and its move history, ready to be stored
+    fn replay_pgn_game(
+        user_id: &str,
+        difficulty: i32,
+        player_color: &str,
+        pgn_game: &PgnGame,
+    ) -> Result<(Game, Vec<GameMove>), String> {
+        // Games from a custom position carry it in the FEN tag, Chess960 games always do
+        let variant = match pgn_game.tag("Variant").map(|v| v.to_lowercase().replace([' ', '-'], "")) {
+            None => "standard".to_string(),
+            Some(v) if v == "standard" => v,
+            Some(v) if v == "chess960" || v == "fischerandom" || v == "fischerrandom" => "chess960".to_string(),
+            Some(_) => return Err(format!("unsupported variant {}", pgn_game.tag("Variant").unwrap_or_default())),
         };
+        let chess960 = variant == "chess960";
+        let start_fen = pgn_game.tag("FEN")
+            .map(|fen| ChessService::validate_fen(fen, chess960))
+            .transpose()?
+            .filter(|fen| chess960 || fen != STARTING_FEN);
+        if chess960 && start_fen.is_none() {
+            return Err("Chess960 game without a FEN tag".to_string());
+        }
+        let mut game = Self::new_game(user_id, difficulty, start_fen, player_color.to_string(), variant);
+        game.engine = EngineRegistry::global().engine_for_new_game(difficulty, None)?;
 
-        sqlx::query!(
-            "INSERT INTO games (id, user_id, difficulty, fen, status, result, created_at, start_time, end_time, duration_seconds, moves_count) 
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
-            game.id,
-            game.user_id,
-            game.difficulty,
-            game.fen,
-            game.status,
-            game.result,
-            game.created_at,
-            game.start_time,
-            game.end_time,
-            game.duration_seconds,
-            game.moves_count
-        )
-        .execute(pool)
-        .await
-        .map_err(|e| format!("Database error: {}", e))?;
-
-        println!("🎯 New game created: {} (Level {})", game.id, game.difficulty);
-        Ok(game)
+        let mut moves = Vec::new();
+        let mut outcome = (false, None, None);
+        for san in &pgn_game.moves {
+            if outcome.0 {
+                return Err(format!("move {} played after the end of the game", san));
+            }
+            let details = ChessService::describe_move(&game.fen, san)
+                .map_err(|e| format!("move {} ({}): {}", game.moves_count + 1, san, e))?;
+            game.fen = ChessService::make_move(&game.fen, &details.uci)?;
+            game.moves_count += 1;
+            moves.push(Self::new_game_move(&game, &details, None));
+            outcome = Self::record_position(&mut game);
+        }
+
+        // The rules decide first; otherwise trust the PGN result (resignation, agreement...)
+        let (game_over, winner, draw_reason) = outcome;
+        let result = if game_over {
+            winner
+        } else {
+            match pgn_game.result.as_str() {
+                "1-0" => Some("white".to_string()),
+                "0-1" => Some("black".to_string()),
+                "1/2-1/2" => Some("draw".to_string()),
+                _ => None,
+            }
+        };
+
+        if result.is_some() {
+            let termination = if game_over {
+                Termination::from_draw_reason(draw_reason.as_deref())
+            } else {
+                Self::pgn_termination(pgn_game, result.as_deref())
+            };
+            Self::finish_imported_game(&mut game, result, termination, draw_reason);
+        }
+
+        Ok((game, moves))
+    }
+
+    /// Marks an imported game as finished (no analysis, no duration: it wasn't played here)
+    fn finish_imported_game(game: &mut Game, result: Option<String>, termination: Termination, draw_reason: Option<String>) {
+        game.termination = Some(termination);
+        game.status = "finished".to_string();
+        game.result = result;
+        game.draw_reason = draw_reason;
+        game.start_time = None;
+        game.end_time = Some(Utc::now());
+    }
+
+    /// Termination of a PGN game that didn't end by the rules, from its Termination tag
+    fn pgn_termination(pgn_game: &PgnGame, result: Option<&str>) -> Termination {
+        match pgn_game.tag("Termination").map(|t| t.to_lowercase()) {
+            Some(t) if t == "time forfeit" => Termination::Timeout,
+            Some(t) if t == "abandoned" => Termination::Aborted,
+            _ if result == Some("draw") => Termination::Agreement,
+            _ => Termination::Resignation,
+        }
     }
 
     /// Retrieves a game by its ID
@@ -63,32 +380,9 @@ impl GameService {
     /// # Returns
     /// Option<Game> - Some(game) if found, None if not found
     pub async fn get_game(pool: &SqlitePool, game_id: &str) -> Result<Option<Game>, String> {
-        let row = sqlx::query!(
-            "SELECT id, user_id, difficulty, fen, status, result, created_at, start_time, end_time, duration_seconds, moves_count FROM games WHERE id = ?",
-            game_id
-        )
-        .fetch_optional(pool)
-        .await
-        .map_err(|e| format!("Database error: {}", e))?;
-
-        if let Some(row) = row {
-let game = Game {
-    id: row.id,
-    user_id: row.user_id,
-    difficulty: row.difficulty as i32,
-    fen: row.fen,
-    status: row.status,
-    result: row.result,
-    created_at: DateTime::<Utc>::from_naive_utc_and_offset(row.created_at, Utc),
-    start_time: row.start_time.map(|dt| DateTime::<Utc>::from_naive_utc_and_offset(dt, Utc)),
-    end_time: row.end_time.map(|dt| DateTime::<Utc>::from_naive_utc_and_offset(dt, Utc)),
-    duration_seconds: row.duration_seconds.map(|d| d as i32),
-    moves_count: row.moves_count as i32,
-};
-            Ok(Some(game))
-        } else {
-            Ok(None)
-        }
+        get_game_by_id(pool, game_id)
+            .await
+            .map_err(|e| format!("Database error: {}", e))
     }
 
     /// Retrieves all games for a specific user
@@ -100,211 +394,168 @@ let game = Game {
     /// # Returns
     /// Vector of games ordered by creation date (newest first)
     pub async fn get_user_games(pool: &SqlitePool, user_id: &str) -> Result<Vec<Game>, String> {
-        let rows = sqlx::query!(
-            "SELECT id, user_id, difficulty, fen, status, result, created_at, start_time, end_time, duration_seconds, moves_count FROM games WHERE user_id = ? ORDER BY created_at DESC",
-            user_id
-        )
-        .fetch_all(pool)
-        .await
-        .map_err(|e| format!("Database error: {}", e))?;
-
-        let games = rows
-            .into_iter()
-            .map(|row| Game {
-                id: row.id,
-                user_id: row.user_id,
-                difficulty: row.difficulty as i32,
-                fen: row.fen,
-                status: row.status,
-                result: row.result,
-                created_at: DateTime::<Utc>::from_naive_utc_and_offset(row.created_at, Utc),
-                start_time: row.start_time.map(|dt| DateTime::<Utc>::from_naive_utc_and_offset(dt, Utc)),
-                end_time: row.end_time.map(|dt| DateTime::<Utc>::from_naive_utc_and_offset(dt, Utc)),
-                duration_seconds: row.duration_seconds.map(|d| d as i32),
-                moves_count: row.moves_count as i32,
-            })
-            .collect();
+        get_games_by_user(pool, user_id)
+            .await
+            .map_err(|e| format!("Database error: {}", e))
+    }
 
-        Ok(games)
+    /// Retrieves the move history of a game
+    /// 
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `game_id` - Unique identifier of the game
+    /// 
+    /// # Returns
+    /// Vector of moves ordered by ply (first move first)
+    pub async fn get_game_moves(pool: &SqlitePool, game_id: &str) -> Result<Vec<GameMove>, String> {
+        get_game_moves(pool, game_id)
+            .await
+            .map_err(|e| format!("Database error: {}", e))
     }
 
     /// Processes a player's move and generates Stockfish response
     /// 
     /// # Arguments
     /// * `pool` - Database connection pool
-    /// * `input` - Move input containing game_id and player_move in algebraic notation
+    /// * `input` - Move input containing game_id and player_move in coordinate notation or SAN
     /// 
     /// # Returns
     /// GameMoveResult containing updated game state and Stockfish's response
     /// 
     /// # Process
-    /// 1. Validates and applies player's move
-    /// 2. Checks if game ends after player's move
-    /// 3. If game continues, gets Stockfish's response
-    /// 4. Checks if game ends after Stockfish's move
-    /// 5. Updates statistics if game finishes
-    /// 6. Saves updated game state to database
+    /// 1. Stops the player's clock (the game is lost on time if it ran out)
+    /// 2. Validates and applies player's move
+    /// 3. Checks if game ends after player's move
+    /// 4. If game continues, gets the game engine's response and charges its thinking time
+    /// 5. Checks if game ends after Stockfish's move
+    /// 6. Updates statistics if game finishes
+    /// 7. Saves updated game state and the played moves to database
     pub async fn make_move(pool: &SqlitePool, input: MakeMoveInput) -> Result<GameMoveResult, String> {
+        Self::make_move_with(pool, input, &EngineRegistry::global()).await
+    }
+
+    /// Same as `make_move`, with the engines taken from the given registry
+    async fn make_move_with(pool: &SqlitePool, input: MakeMoveInput, engines: &EngineRegistry) -> Result<GameMoveResult, String> {
         println!("🎮 Processing move: {} in game {}", input.player_move, input.game_id);
 
         // Fetch current game state
-        let row = sqlx::query!(
-            "SELECT id, user_id, difficulty, fen, status, result, created_at, start_time, end_time, duration_seconds, moves_count FROM games WHERE id = ?",
-            input.game_id
-        )
-        .fetch_one(pool)
-        .await
-        .map_err(|e| format!("Game not found: {}", e))?;
-
-        let mut game = Game {
-            id: row.id,
-            user_id: row.user_id,
-            difficulty: row.difficulty as i32,
-            fen: row.fen,
-            status: row.status,
-            result: row.result,
-            created_at: DateTime::<Utc>::from_naive_utc_and_offset(row.created_at, Utc),
-            start_time: row.start_time.map(|dt| DateTime::<Utc>::from_naive_utc_and_offset(dt, Utc)),
-            end_time: row.end_time.map(|dt| DateTime::<Utc>::from_naive_utc_and_offset(dt, Utc)),
-            duration_seconds: row.duration_seconds.map(|d| d as i32),
-            moves_count: row.moves_count as i32,
-        };
+        let mut game = Self::get_active_game(pool, &input.game_id).await?;
 
-        if game.status != "active" {
-            return Err("Game is not active".to_string());
+        if !Self::is_player_turn(&game) {
+            return Err("Not your turn".to_string());
         }
+        // Saved only if no other move was stored for this game meanwhile
+        let stored_moves_count = game.moves_count;
 
-        // Apply player's move
-        let new_fen = ChessService::make_move(&game.fen, &input.player_move)
-            .map_err(|e| format!("Illegal move: {}", e))?;
+        // The player's think time runs from the start of the turn (older games: from the
+        // previous move or the start of the game)
+        let turn_started_at = match game.turn_started_at {
+            Some(started) => Some(started),
+            None => get_game_moves(pool, &game.id)
+                .await
+                .map_err(|e| format!("Database error: {}", e))?
+                .last()
+                .map(|m| m.created_at)
+                .or(game.start_time),
+        };
+        let move_time_ms = turn_started_at.map(|t| (Utc::now() - t).num_milliseconds().max(0));
+        let player_think_ms = move_time_ms.map(|ms| ms as i32);
+
+        // Flag fall: the player's time ran out before this move arrived
+        let player_color = game.player_color.clone();
+        if !ClockService::press(&mut game, &player_color, move_time_ms.unwrap_or(0)) {
+            Self::lose_on_time(pool, &mut game).await?;
+            return Err("Time is up: the game was lost on time".to_string());
+        }
 
+        // Apply player's move (SAN is accepted and normalized to coordinate notation)
+        let player_details = ChessService::describe_move(&game.fen, &input.player_move)?;
+        game.fen = ChessService::make_move(&game.fen, &player_details.uci)?;
         game.moves_count += 1;
-        game.fen = new_fen.clone();
+
+        let mut played_moves = vec![Self::new_game_move(&game, &player_details, player_think_ms)];
 
         // Check if game ends after player's move
-        let (game_over, winner) = ChessService::check_game_over(&new_fen);
+        let (game_over, winner, draw_reason) = Self::record_position(&mut game);
         
         let stockfish_move: String;
-        let mut last_move_piece_type = "unknown".to_string();
+        let mut engine_details = None;
         
         if game_over {
             // Game ends, update final state
-            game.status = "finished".to_string();
-            game.result = winner.clone();
-            game.end_time = Some(Utc::now());
-            
-            if let Some(start_time) = game.start_time {
-                let duration = (Utc::now() - start_time).num_seconds() as i32;
-                game.duration_seconds = Some(duration);
-                
-                let won = winner.as_deref() == Some("white");
-                StatsService::update_game_stats(
-                    pool,
-                    &game.user_id,
-                    game.difficulty,
-                    duration,
-                    game.moves_count,
-                    won,
-                ).await.map_err(|e| format!("Stats update error: {}", e))?;
-            }
+            let termination = Termination::from_draw_reason(draw_reason.as_deref());
+            Self::finish_game(&mut game, winner.clone(), termination);
             
             stockfish_move = "none".to_string();
             println!("🏁 Game finished! Winner: {:?}", winner);
         } else {
             // Game continues, get Stockfish response
-            stockfish_move = StockfishService::get_best_move(&new_fen, game.difficulty)
-                .await
-                .map_err(|e| format!("Stockfish error: {}", e))?;
-
-            println!("🤖 Stockfish plays: {}", stockfish_move);
-            println!("🎯 Move breakdown: from='{}', to='{}'", &stockfish_move[0..2], &stockfish_move[2..4]);
-
-            // Get the piece type that's moving BEFORE applying the move
-            let piece_type = if stockfish_move.len() >= 4 {
-                let from_square = &stockfish_move[0..2];
-                println!("🔍 Looking for piece at square: {}", from_square);
-                // Utiliser la FEN AVANT le coup de Stockfish (c'est game.fen ici)
-                let piece = ChessService::get_piece_at_square(&game.fen, from_square)
-                    .unwrap_or_else(|| "piece".to_string());
-                println!("🎲 Found piece: {}", piece);
-                piece
-            } else {
-                "unknown".to_string()
-            };
+            let position_before = (game.fen.clone(), game.moves_count);
+            let engine = engines.engine_for_game(&game);
+            let (details, game_move) = Self::play_engine_move(&mut game, engine.as_ref()).await?;
+            let engine_think_ms = game_move.think_time_ms.unwrap_or(0) as i64;
 
-            // Apply Stockfish's move
-            game.fen = ChessService::make_move(&new_fen, &stockfish_move)
-                .map_err(|e| format!("Stockfish move error: {}", e))?;
-
-            game.moves_count += 1;
+            let engine_color = Self::engine_color(&game);
+            if !ClockService::press(&mut game, engine_color, engine_think_ms) {
+                // The engine's flag fell before its move reached the board
+                (game.fen, game.moves_count) = position_before;
+                Self::finish_game(&mut game, Some(player_color.clone()), Termination::Timeout);
+                stockfish_move = "none".to_string();
+                println!("⏱️ Stockfish lost on time");
+            } else {
+                stockfish_move = details.uci.clone();
+                played_moves.push(game_move);
 
-            // Check if game ends after Stockfish's move
-            let (sf_game_over, sf_winner) = ChessService::check_game_over(&game.fen);
-            if sf_game_over {
-                game.status = "finished".to_string();
-                game.result = sf_winner.clone();
-                game.end_time = Some(Utc::now());
-                
-                if let Some(start_time) = game.start_time {
-                    let duration = (Utc::now() - start_time).num_seconds() as i32;
-                    game.duration_seconds = Some(duration);
-                    
-                    let won = sf_winner.as_deref() == Some("white");
-                    StatsService::update_game_stats(
-                        pool,
-                        &game.user_id,
-                        game.difficulty,
-                        duration,
-                        game.moves_count,
-                        won,
-                    ).await.map_err(|e| format!("Stats update error: {}", e))?;
+                // Check if game ends after Stockfish's move
+                let (sf_game_over, sf_winner, sf_draw_reason) = Self::record_position(&mut game);
+                if sf_game_over {
+                    let termination = Termination::from_draw_reason(sf_draw_reason.as_deref());
+                    Self::finish_game(&mut game, sf_winner.clone(), termination);
+                    println!("🏁 Game finished after Stockfish move! Winner: {:?}", sf_winner);
                 }
-                println!("🏁 Game finished after Stockfish move! Winner: {:?}", sf_winner);
+
+                engine_details = Some(details);
             }
+        }
 
-            // Store the piece type for animation
-            last_move_piece_type = piece_type;
-        }
-
-        // Save updated game state
-        sqlx::query!(
-            "UPDATE games SET fen = ?, status = ?, result = ?, end_time = ?, duration_seconds = ?, moves_count = ? WHERE id = ?",
-            game.fen,
-            game.status,
-            game.result,
-            game.end_time,
-            game.duration_seconds,
-            game.moves_count,
-            game.id
-        )
-        .execute(pool)
-        .await
-        .map_err(|e| format!("Database update error: {}", e))?;
-
-    let total_time_seconds = if let Some(start_time) = game.start_time {
-        Some((Utc::now() - start_time).num_seconds() as i32)
-    } else {
-        None
-    };
+        // The player's clock starts running
+        game.turn_started_at = Some(Utc::now());
+
+        // Save updated game state together with the played moves
+        let mut tx = pool.begin().await.map_err(|e| format!("Database update error: {}", e))?;
+        let saved = update_game_from_ply(&mut *tx, &game, stored_moves_count)
+            .await
+            .map_err(|e| format!("Database update error: {}", e))?;
+        if !saved {
+            // Another request moved first (the transaction is rolled back when dropped)
+            return Err("Not your turn".to_string());
+        }
+        for game_move in &played_moves {
+            insert_game_move(&mut *tx, game_move)
+                .await
+                .map_err(|e| format!("Database update error: {}", e))?;
+        }
+        tx.commit().await.map_err(|e| format!("Database update error: {}", e))?;
+        Self::record_finished_game(pool, &game).await?;
+
+    let total_time_seconds = game.start_time
+        .map(|start_time| (Utc::now() - start_time).num_seconds() as i32);
 
     // Clone le result AVANT de déplacer game
     let winner = game.result.clone();
+    let draw_reason = game.draw_reason.clone();
     let game_over = game.status == "finished";
 
     // Créer lastMove pour l'animation avec le coup de Stockfish (seul coup à animer)
-    let last_move = if stockfish_move != "none" {
-        let from_square = stockfish_move[0..2].to_string();
-        let to_square = stockfish_move[2..4].to_string();
-        println!("🎬 Creating LastMove: {} -> {} (piece: {}, color: black)", from_square, to_square, last_move_piece_type);
-        Some(crate::models::game::LastMove {
-            from: from_square,
-            to: to_square,
-            piece: last_move_piece_type,
-            color: "black".to_string(),
-        })
-    } else {
-        None
-    };
+    let last_move = engine_details.as_ref().map(|details| {
+        println!("🎬 Creating LastMove: {} -> {} (piece: {}, color: {})", details.from, details.to, details.piece, details.color);
+        crate::models::game::LastMove {
+            from: details.from.clone(),
+            to: details.to.clone(),
+            piece: details.piece.clone(),
+            color: details.color.clone(),
+        }
+    });
 
     println!("✅ Move processed successfully");
 
@@ -313,9 +564,456 @@ let game = Game {
         stockfish_move,
         game_over,
         winner,
-        move_time_ms: None,
+        draw_reason,
+        move_time_ms,
         total_time_seconds,
         last_move,
+        player_move: player_details,
+        engine_move: engine_details,
     })
     }
-}
\ No newline at end of file
+
+    /// Takes back the player's last move together with the engine's reply
+    /// 
+    /// The position, move count and repetition history are restored from the stored move
+    /// history, and the game's takeback counter is incremented.
+    /// 
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `game_id` - Unique identifier of the game
+    /// 
+    /// # Returns
+    /// The game as it was before the player's last move
+    pub async fn take_back(pool: &SqlitePool, game_id: &str) -> Result<Game, String> {
+        let mut game = Self::get_active_game(pool, game_id).await?;
+
+        if !game.takebacks_allowed {
+            return Err("Takebacks are disabled for this game".to_string());
+        }
+
+        let mut moves = get_game_moves(pool, &game.id)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?;
+
+        // Remove the engine's reply (if any), then the player's move
+        let mut removed = 0;
+        if moves.last().is_some_and(|m| m.side != game.player_color) {
+            moves.pop();
+            removed += 1;
+        }
+        let player_move = moves.pop().ok_or_else(|| "No move to take back".to_string())?;
+        removed += 1;
+        let stored_moves_count = game.moves_count;
+
+        game.fen = match moves.last() {
+            Some(previous) => previous.fen_after.clone(),
+            None => game.start_fen.clone().unwrap_or_else(|| STARTING_FEN.to_string()),
+        };
+        game.moves_count = player_move.ply - 1;
+        game.takebacks += 1;
+        game.turn_started_at = Some(Utc::now());
+
+        let mut history: Vec<&str> = game.position_history.lines().collect();
+        history.truncate(history.len().saturating_sub(removed));
+        game.position_history = history.join("\n");
+
+        let mut tx = pool.begin().await.map_err(|e| format!("Database update error: {}", e))?;
+        let saved = update_game_from_ply(&mut *tx, &game, stored_moves_count)
+            .await
+            .map_err(|e| format!("Database update error: {}", e))?;
+        if !saved {
+            return Err(STALE_GAME_ERROR.to_string());
+        }
+        delete_game_moves_from(&mut *tx, &game.id, player_move.ply)
+            .await
+            .map_err(|e| format!("Database update error: {}", e))?;
+        tx.commit().await.map_err(|e| format!("Database update error: {}", e))?;
+
+        println!("↩️ Took back {} in game {} ({} takebacks)", player_move.san, game.id, game.takebacks);
+        Ok(game)
+    }
+
+    /// Suggests the best move to the player with a full-strength search
+    /// 
+    /// The game's difficulty doesn't weaken the search. Every hint is counted on the game,
+    /// and a game with hints cannot set a personal record.
+    /// 
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `game_id` - Unique identifier of the game
+    /// 
+    /// # Returns
+    /// The best move with its evaluation and principal variation
+    pub async fn get_hint(pool: &SqlitePool, game_id: &str) -> Result<Hint, String> {
+        let mut game = Self::get_active_game(pool, game_id).await?;
+
+        if !Self::is_player_turn(&game) {
+            return Err("Not your turn".to_string());
+        }
+        let stored_moves_count = game.moves_count;
+
+        // Without Stockfish, the built-in engine's move comes with a one-move line
+        let analysis = Self::analyse_position(&game.fen, Some(HINT_DEPTH), Some(HINT_MOVETIME_MS)).await?;
+        let best_move = analysis
+            .best_move
+            .ok_or_else(|| "The engine returned no move".to_string())?;
+        let best_move = ChessService::describe_move(&game.fen, &best_move)
+            .map_err(|e| format!("The engine returned an invalid move {}: {}", best_move, e))?;
+
+        let line = analysis.lines.first();
+        let (evaluation_cp, mate_in) = match line.map(|l| l.score.for_white(&game.fen)) {
+            Some(EngineScore::Cp(cp)) => (Some(cp), None),
+            Some(EngineScore::Mate(moves)) => (None, Some(moves)),
+            None => (None, None),
+        };
+        let pv = match line {
+            Some(line) if line.pv.first() == Some(&best_move.uci) => ChessService::line_to_san(&game.fen, &line.pv),
+            _ => vec![best_move.san.clone()],
+        };
+
+        // The hint is only worth counting (and showing) for the position it was searched on
+        game.hints_used += 1;
+        let saved = update_game_from_ply(pool, &game, stored_moves_count)
+            .await
+            .map_err(|e| format!("Database update error: {}", e))?;
+        if !saved {
+            return Err(STALE_GAME_ERROR.to_string());
+        }
+
+        println!("💡 Hint {} in game {} ({} hints)", best_move.san, game.id, game.hints_used);
+        Ok(Hint {
+            best_move,
+            evaluation_cp,
+            mate_in,
+            pv,
+            depth: line.map_or(0, |l| l.depth),
+            hints_used: game.hints_used,
+        })
+    }
+
+    /// Resigns a game on behalf of the player; the engine wins
+    /// 
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `game_id` - Unique identifier of the game
+    /// 
+    /// # Returns
+    /// The finished game
+    pub async fn resign_game(pool: &SqlitePool, game_id: &str) -> Result<Game, String> {
+        let mut game = Self::get_active_game(pool, game_id).await?;
+        let stored_moves_count = game.moves_count;
+
+        let winner = Self::engine_color(&game).to_string();
+        Self::finish_game(&mut game, Some(winner), Termination::Resignation);
+
+        // A move ending the game may have been saved meanwhile: its result stands
+        let saved = update_game_from_ply(pool, &game, stored_moves_count)
+            .await
+            .map_err(|e| format!("Database update error: {}", e))?;
+        if !saved {
+            return Err(STALE_GAME_ERROR.to_string());
+        }
+        Self::record_finished_game(pool, &game).await?;
+
+        println!("🏳️ Game {} resigned", game.id);
+        Ok(game)
+    }
+
+    /// Aborts a game that has barely started: no result and no statistics
+    /// 
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `game_id` - Unique identifier of the game
+    /// 
+    /// # Returns
+    /// The aborted game, or an error once more than ABORT_MAX_PLIES plies were played
+    pub async fn abort_game(pool: &SqlitePool, game_id: &str) -> Result<Game, String> {
+        let mut game = Self::get_active_game(pool, game_id).await?;
+
+        if game.moves_count > ABORT_MAX_PLIES {
+            return Err(format!(
+                "Game can only be aborted during its first {} plies, resign instead",
+                ABORT_MAX_PLIES
+            ));
+        }
+
+        game.status = "aborted".to_string();
+        game.termination = Some(Termination::Aborted);
+        game.end_time = Some(Utc::now());
+        game.duration_seconds = game.start_time.map(|start_time| (Utc::now() - start_time).num_seconds() as i32);
+
+        update_game(pool, &game)
+            .await
+            .map_err(|e| format!("Database update error: {}", e))?;
+
+        println!("🚫 Game {} aborted", game.id);
+        Ok(game)
+    }
+
+    /// Offers a draw to the engine, which accepts when its position is not good enough
+    /// for its level (see `draw_acceptance_margin`)
+    /// 
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `game_id` - Unique identifier of the game
+    /// 
+    /// # Returns
+    /// Whether the offer was accepted, the engine's evaluation and the (possibly drawn) game
+    pub async fn offer_draw(pool: &SqlitePool, game_id: &str) -> Result<DrawOfferResult, String> {
+        let mut game = Self::get_active_game(pool, game_id).await?;
+        let stored_moves_count = game.moves_count;
+
+        // Scores are for the side to move
+        let analysis = Self::analyse_position(&game.fen, Some(DRAW_OFFER_DEPTH), None).await?;
+        let evaluation = analysis.lines.first().map_or(0, |line| line.score.as_cp());
+        let engine_evaluation = if Self::is_player_turn(&game) { -evaluation } else { evaluation };
+
+        let accepted = engine_evaluation <= Self::draw_acceptance_margin(game.difficulty);
+        if accepted {
+            Self::finish_game(&mut game, Some("draw".to_string()), Termination::Agreement);
+            // The offer was judged on the position read above
+            let saved = update_game_from_ply(pool, &game, stored_moves_count)
+                .await
+                .map_err(|e| format!("Database update error: {}", e))?;
+            if !saved {
+                return Err(STALE_GAME_ERROR.to_string());
+            }
+            Self::record_finished_game(pool, &game).await?;
+        }
+
+        println!("🤝 Draw offer in game {}: {} (engine eval {})", game.id, if accepted { "accepted" } else { "declined" }, engine_evaluation);
+        Ok(DrawOfferResult { accepted, engine_evaluation, game })
+    }
+
+    /// Analyses a position with Stockfish, or with the built-in engine when Stockfish is
+    /// missing or returns nothing (shallower, with a one-move line)
+    ///
+    /// # Arguments
+    /// * `fen` - Position to analyse
+    /// * `depth` - Maximum Stockfish depth
+    /// * `movetime_ms` - Maximum Stockfish search time
+    ///
+    /// # Returns
+    /// Result<EngineAnalysis, String> - Best move and line, scores for the side to move
+    async fn analyse_position(fen: &str, depth: Option<i32>, movetime_ms: Option<i32>) -> Result<EngineAnalysis, String> {
+        match StockfishService::analyse(fen, depth, movetime_ms, 1).await {
+            Ok(analysis) if analysis.best_move.is_some() || !analysis.lines.is_empty() => Ok(analysis),
+            result => {
+                let reason = result.err().unwrap_or_else(|| "no result".to_string());
+                println!("⚠️ Stockfish unavailable ({}), analysing with the built-in engine", reason);
+                let fen = fen.to_string();
+                let limits = BuiltinSearch::limits_for_level(20);
+                tokio::task::spawn_blocking(move || BuiltinSearch::analyse(&fen, &limits))
+                    .await
+                    .map_err(|e| format!("Built-in engine failed: {}", e))?
+            }
+        }
+    }
+
+    /// Highest evaluation (centipawns, from the engine's side) at which the engine still
+    /// accepts a draw: low levels take a draw even when better, level 20 only when equal or worse
+    fn draw_acceptance_margin(difficulty: i32) -> i32 {
+        (20 - difficulty.clamp(1, 20)) * 15
+    }
+
+    /// Ends every active game whose player has run out of time
+    /// 
+    /// Run periodically in the background so games are flagged even when the player never
+    /// comes back to move.
+    /// 
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// 
+    /// # Returns
+    /// Number of games lost on time
+    pub async fn flag_expired_games(pool: &SqlitePool) -> Result<usize, String> {
+        let games = get_active_timed_games(pool)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?;
+
+        let now = Utc::now();
+        let mut flagged = 0;
+        for mut game in games {
+            // The engine's clock is checked when its move comes back in make_move
+            if !Self::is_player_turn(&game) {
+                continue;
+            }
+            if ClockService::remaining_ms(&game, &game.player_color, now).is_some_and(|left| left <= 0) {
+                Self::lose_on_time(pool, &mut game).await?;
+                flagged += 1;
+            }
+        }
+        Ok(flagged)
+    }
+
+    /// Ends a game lost on time by the player and saves it
+    async fn lose_on_time(pool: &SqlitePool, game: &mut Game) -> Result<(), String> {
+        if game.player_color == "white" {
+            game.white_clock_ms = Some(0);
+        } else {
+            game.black_clock_ms = Some(0);
+        }
+
+        let winner = Self::engine_color(game).to_string();
+        Self::finish_game(game, Some(winner), Termination::Timeout);
+        update_game(pool, game)
+            .await
+            .map_err(|e| format!("Database update error: {}", e))?;
+        Self::record_finished_game(pool, game).await?;
+
+        println!("⏱️ Game {} lost on time", game.id);
+        Ok(())
+    }
+
+    /// Fetches a game that is still being played
+    async fn get_active_game(pool: &SqlitePool, game_id: &str) -> Result<Game, String> {
+        let game = get_game_by_id(pool, game_id)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?
+            .ok_or_else(|| "Game not found".to_string())?;
+
+        if game.status != "active" {
+            return Err("Game is not active".to_string());
+        }
+        Ok(game)
+    }
+
+    /// Asks the game's engine for its move in the current position and applies it
+    /// 
+    /// # Returns
+    /// The move description and its history entry (not yet stored)
+    async fn play_engine_move(game: &mut Game, engine: &dyn ChessEngine) -> Result<(MoveDetails, GameMove), String> {
+        let profile = DifficultyProfiles::global().for_game(game);
+        let think_start = Utc::now();
+        let stockfish_move = engine
+            .best_move(&game.fen, &profile, game.personality, move_seed(game.rng_seed, game.moves_count))
+            .await
+            .map_err(|e| format!("{} error: {}", engine.display_name(), e))?;
+        let stockfish_think_ms = (Utc::now() - think_start).num_milliseconds() as i32;
+
+        println!("🤖 Stockfish plays: {}", stockfish_move);
+
+        // Describe the move BEFORE applying it (piece, capture, check...)
+        let details = ChessService::describe_move(&game.fen, &stockfish_move)
+            .map_err(|e| format!("Stockfish move error: {}", e))?;
+        println!("🎯 Move breakdown: {} {} -> {} ({})", details.piece, details.from, details.to, details.san);
+
+        // Apply Stockfish's move
+        game.fen = ChessService::make_move(&game.fen, &details.uci)
+            .map_err(|e| format!("Stockfish move error: {}", e))?;
+        game.moves_count += 1;
+
+        let game_move = Self::new_game_move(game, &details, Some(stockfish_think_ms));
+        Ok((details, game_move))
+    }
+
+    /// Builds the history entry for the move that just produced the game's current position
+    /// 
+    /// # Arguments
+    /// * `game` - Game after the move was applied (fen and moves_count already updated)
+    /// * `details` - Description of the move
+    /// * `think_time_ms` - Time spent choosing the move
+    fn new_game_move(game: &Game, details: &MoveDetails, think_time_ms: Option<i32>) -> GameMove {
+        GameMove {
+            id: Uuid::new_v4().to_string(),
+            game_id: game.id.clone(),
+            ply: game.moves_count,
+            side: details.color.clone(),
+            uci: details.uci.clone(),
+            san: details.san.clone(),
+            fen_after: game.fen.clone(),
+            think_time_ms,
+            created_at: Utc::now(),
+        }
+    }
+
+    /// Checks the game's current position against the end-of-game rules, then appends it
+    /// to the position history used for threefold repetition
+    /// 
+    /// # Returns
+    /// (bool, Option<String>, Option<String>) - (is_game_over, winner, draw_reason)
+    fn record_position(game: &mut Game) -> (bool, Option<String>, Option<String>) {
+        let history: Vec<String> = game.position_history.lines().map(|l| l.to_string()).collect();
+        let status = ChessService::check_game_over(&game.fen, &history);
+
+        if let Some(key) = ChessService::position_key(&game.fen) {
+            if !game.position_history.is_empty() {
+                game.position_history.push('\n');
+            }
+            game.position_history.push_str(&key);
+        }
+
+        status
+    }
+
+    /// Records a finished game in the player's statistics
+    /// 
+    /// Chess960 games and games from a custom position go to their own mode statistics
+    /// instead of the regular level statistics, records and streaks.
+    /// 
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `game` - Finished game
+    /// * `duration` - Game duration in seconds
+    /// * `record_eligible` - Whether a win may set a personal record
+    async fn update_stats(pool: &SqlitePool, game: &Game, duration: i32, record_eligible: bool) -> Result<(), String> {
+        let won = game.result.as_deref() == Some(game.player_color.as_str());
+
+        let mode = if game.variant == "chess960" { Some("chess960") } else { game.start_fen.as_ref().map(|_| "custom_position") };
+
+        if let Some(mode) = mode {
+            StatsService::update_mode_stats(
+                pool,
+                &game.user_id,
+                mode,
+                game.difficulty,
+                duration,
+                game.moves_count,
+                won,
+            ).await
+        } else {
+            StatsService::update_game_stats(
+                pool,
+                &game.user_id,
+                game.difficulty,
+                duration,
+                game.moves_count,
+                won,
+                record_eligible,
+            ).await
+        }
+        .map_err(|e| format!("Stats update error: {}", e))
+    }
+
+    /// Marks a game as finished (statistics follow with `record_finished_game` once it is saved)
+    /// 
+    /// # Arguments
+    /// * `game` - Game to finish (modified in place, not saved)
+    /// * `winner` - "white", "black" or "draw"
+    /// * `termination` - How the game ended
+    fn finish_game(game: &mut Game, winner: Option<String>, termination: Termination) {
+        game.draw_reason = (winner.as_deref() == Some("draw")).then(|| termination.as_str().to_string());
+        game.status = "finished".to_string();
+        game.result = winner;
+        game.termination = Some(termination);
+        game.end_time = Some(Utc::now());
+        // Picked up by the background analysis worker once the game is saved
+        game.analysis_status = Some(AnalysisStatus::Pending);
+        game.duration_seconds = game.start_time.map(|start_time| (Utc::now() - start_time).num_seconds() as i32);
+
+        if let Some(reason) = &game.draw_reason {
+            println!("🤝 Draw by {}", reason);
+        }
+    }
+
+    /// Updates the player's statistics after a game finished by `finish_game` was saved
+    /// (nothing to do for a game still being played)
+    async fn record_finished_game(pool: &SqlitePool, game: &Game) -> Result<(), String> {
+        if game.status != "finished" || game.start_time.is_none() {
+            return Ok(());
+        }
+        // Taking moves back or asking for hints rules out personal records
+        let record_eligible = game.takebacks == 0 && game.hints_used == 0;
+        Self::update_stats(pool, game, game.duration_seconds.unwrap_or(0), record_eligible).await
+    }
+}
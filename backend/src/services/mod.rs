@@ -1,11 +1,27 @@
 pub mod chess_service;
 pub mod stockfish_service;
+pub mod engine_pool;
+pub mod engines;
+pub mod builtin_search;
+pub mod personality_service;
 pub mod game_service;
 pub mod stats_service;
 pub mod user_service;
+pub mod pgn_service;
+pub mod clock_service;
+pub mod analysis_service;
+pub mod difficulty_service;
+pub mod calibration_service;
 
 pub use chess_service::ChessService;
 pub use stockfish_service::StockfishService;
+pub use engines::EngineRegistry;
 pub use game_service::GameService;
 pub use stats_service::StatsService;
-pub use user_service::UserService;
\ No newline at end of file
+pub use user_service::UserService;
+pub use pgn_service::PgnService;
+pub use clock_service::ClockService;
+pub use analysis_service::AnalysisService;
+pub use difficulty_service::{DifficultyProfiles, DifficultyService};
+pub use calibration_service::CalibrationService;
+pub use personality_service::PersonalityService;
\ No newline at end of file
@@ -0,0 +1,240 @@
+use std::str::FromStr;
+use chess::{BitBoard, Board, ChessMove, Color, MoveGen, Piece, Square};
+use tracing::debug;
+use crate::models::Personality;
+use crate::services::ChessService;
+use crate::services::builtin_search::PIECE_VALUES;
+use crate::services::stockfish_service::{EngineLine, EngineScore};
+
+/// Candidates scoring further than this from the engine's choice are never preferred
+pub const PERSONALITY_MARGIN_CP: i32 = 50;
+
+/// Candidate moves Stockfish is asked for when the bot has a personality
+pub const PERSONALITY_MULTIPV: i32 = 6;
+
+/// Plies of a candidate's line looked at for trades and captures
+const LINE_PLIES: usize = 4;
+
+/// Moves up to this one count as the opening
+const OPENING_MOVES: u32 = 10;
+
+/// What a candidate move does, as far as personalities care
+#[derive(Debug, Default)]
+struct MoveTraits {
+    capture: bool,
+    check: bool,
+    castling: bool,
+    /// Knight or bishop leaving its back rank
+    development: bool,
+    /// Lands on d4, e4, d5 or e5
+    centre: bool,
+    /// Lands at most two squares from the enemy king
+    near_enemy_king: bool,
+    /// Piece (not a pawn or the king) moving back towards its own side
+    retreat: bool,
+    /// Queen move during the opening
+    early_queen_move: bool,
+    /// Pawn move in front of the king once it has left the centre
+    king_pawn_push: bool,
+    /// Captures in the first plies of the line
+    line_captures: i32,
+    /// Both sides lost material in the first plies of the line
+    trade: bool,
+    /// Both queens left the board in the first plies of the line
+    queen_trade: bool,
+    /// The opponent can take a piece that looks free (undefended, or taken by a cheaper piece)
+    bait: bool,
+    /// The line's reply takes that piece
+    bait_taken: bool,
+}
+
+/// Service biasing the bot's moves towards a personality
+pub struct PersonalityService;
+
+impl PersonalityService {
+    /// Picks the move the personality prefers among the engine's candidates
+    ///
+    /// Only candidates scoring within `PERSONALITY_MARGIN_CP` of the engine's choice are
+    /// considered, so the bot keeps its strength; mates are played as the engine found them.
+    ///
+    /// # Arguments
+    /// * `fen` - Position searched
+    /// * `lines` - Candidates reported by the engine (scores for the side to move)
+    /// * `chosen` - Move the engine chose
+    /// * `personality` - Style to play with
+    ///
+    /// # Returns
+    /// String - Preferred move (`chosen` when no candidate suits the style better)
+    pub fn pick_move(fen: &str, lines: &[EngineLine], chosen: &str, personality: Personality) -> String {
+        if personality == Personality::Balanced {
+            return chosen.to_string();
+        }
+        let Ok(board) = ChessService::load_board(fen) else {
+            return chosen.to_string();
+        };
+        // The engine's choice may be missing from the candidates (e.g., a late bestmove)
+        let Some(reference) = lines.iter().find(|line| line.pv.first().map(String::as_str) == Some(chosen)) else {
+            return chosen.to_string();
+        };
+        if matches!(reference.score, EngineScore::Mate(_)) {
+            return chosen.to_string();
+        }
+
+        let reference_cp = reference.score.as_cp();
+        let mut preferred = chosen.to_string();
+        let mut preferred_value = reference_cp + Self::bonus(&board, fen, &reference.pv, personality);
+        for line in lines {
+            let Some(candidate) = line.pv.first() else { continue };
+            if candidate == chosen
+                || matches!(line.score, EngineScore::Mate(_))
+                || (line.score.as_cp() - reference_cp).abs() > PERSONALITY_MARGIN_CP
+            {
+                continue;
+            }
+            let value = line.score.as_cp() + Self::bonus(&board, fen, &line.pv, personality);
+            if value > preferred_value {
+                preferred = candidate.clone();
+                preferred_value = value;
+            }
+        }
+
+        if preferred != chosen {
+            debug!("Personality {}: {} instead of {}", personality.as_str(), preferred, chosen);
+        }
+        preferred
+    }
+
+    /// Centipawns a personality adds to a candidate's score (0 for an illegal line)
+    fn bonus(board: &Board, fen: &str, pv: &[String], personality: Personality) -> i32 {
+        let Some(t) = Self::traits(board, fen, pv) else {
+            return 0;
+        };
+        let flag = |set: bool, value: i32| if set { value } else { 0 };
+        match personality {
+            Personality::Balanced => 0,
+            Personality::Aggressive => {
+                flag(t.capture, 25) + flag(t.check, 30) + flag(t.near_enemy_king, 10) + 8 * t.line_captures
+                    - flag(t.queen_trade, 40)
+            }
+            Personality::Positional => {
+                flag(!t.capture && !t.check, 10) + flag(t.development, 20) + flag(t.castling, 25) + flag(t.centre, 10)
+                    - flag(t.early_queen_move, 20)
+            }
+            Personality::Defensive => {
+                flag(t.trade, 20) + flag(t.queen_trade, 30) + flag(t.castling, 25) + flag(t.retreat, 10)
+                    - flag(t.king_pawn_push, 20)
+            }
+            Personality::Trappy => {
+                flag(t.bait && !t.bait_taken, 50) + flag(t.bait && t.bait_taken, 20) + flag(t.check, 10)
+                    + 5 * t.line_captures
+            }
+        }
+    }
+
+    /// Describes a candidate from its line (None when its first move is illegal)
+    fn traits(board: &Board, fen: &str, pv: &[String]) -> Option<MoveTraits> {
+        let chess_move = ChessService::parse_move(fen, pv.first()?).ok()?;
+        let color = board.side_to_move();
+        let from = chess_move.get_source();
+        let to = chess_move.get_dest();
+        let piece = board.piece_on(from)?;
+        let after = ChessService::play(board, chess_move).ok()?;
+
+        let file_distance = (from.get_file().to_index() as i32 - to.get_file().to_index() as i32).abs();
+        let castling = piece == Piece::King && (board.color_on(to) == Some(color) || file_distance == 2);
+        let capture = Self::is_capture(board, chess_move);
+        // Distance along the board towards the opponent's side
+        let progress = |square: Square| match color {
+            Color::White => square.get_rank().to_index() as i32,
+            Color::Black => 7 - square.get_rank().to_index() as i32,
+        };
+        let own_king = board.king_square(color);
+        let king_castled_side = !(3..=4).contains(&own_king.get_file().to_index());
+        let fullmove: u32 = fen.split_whitespace().nth(5).and_then(|n| n.parse().ok()).unwrap_or(1);
+
+        let mut traits = MoveTraits {
+            capture,
+            check: after.checkers().popcnt() > 0,
+            castling,
+            development: matches!(piece, Piece::Knight | Piece::Bishop) && from.get_rank() == color.to_my_backrank(),
+            centre: !castling && [Square::D4, Square::E4, Square::D5, Square::E5].contains(&to),
+            near_enemy_king: !castling && Self::distance(to, board.king_square(!color)) <= 2,
+            retreat: !matches!(piece, Piece::Pawn | Piece::King) && progress(to) < progress(from),
+            early_queen_move: piece == Piece::Queen && fullmove <= OPENING_MOVES,
+            king_pawn_push: piece == Piece::Pawn
+                && king_castled_side
+                && (from.get_file().to_index() as i32 - own_king.get_file().to_index() as i32).abs() <= 1,
+            ..MoveTraits::default()
+        };
+
+        // Trades: follow the line for a few plies (it stops at a move it can't play, e.g.
+        // Chess960 castling written as king takes rook)
+        let mut current = after;
+        let mut line_captures = i32::from(capture);
+        for uci in pv.iter().skip(1).take(LINE_PLIES - 1) {
+            let Ok(next) = ChessMove::from_str(uci) else { break };
+            if !current.legal(next) {
+                break;
+            }
+            line_captures += i32::from(Self::is_capture(&current, next));
+            current = current.make_move_new(next);
+        }
+        traits.line_captures = line_captures;
+        let lost = |side: Color| Self::material(board, side) - Self::material(&current, side);
+        traits.trade = lost(color) > 0 && lost(!color) > 0;
+        let queens = |position: &Board, side: Color| (position.pieces(Piece::Queen) & position.color_combined(side)).popcnt();
+        traits.queen_trade = queens(&current, color) < queens(board, color) && queens(&current, !color) < queens(board, !color);
+
+        // Bait: a piece (not a pawn) the opponent can take, apparently for free
+        let bait: Vec<ChessMove> = MoveGen::new_legal(&after)
+            .filter(|reply| Self::is_capture(&after, *reply))
+            .filter(|reply| {
+                let victim = Self::value(after.piece_on(reply.get_dest()));
+                let attacker = Self::value(after.piece_on(reply.get_source()));
+                victim >= PIECE_VALUES[Piece::Knight.to_index()] && (victim > attacker || !Self::defended(&after, *reply))
+            })
+            .collect();
+        traits.bait = !bait.is_empty();
+        traits.bait_taken = pv
+            .get(1)
+            .and_then(|uci| ChessMove::from_str(uci).ok())
+            .is_some_and(|reply| bait.contains(&reply));
+
+        Some(traits)
+    }
+
+    /// Whether the side that moved can take back on the square of a capture
+    fn defended(board: &Board, capture: ChessMove) -> bool {
+        let taken = board.make_move_new(capture);
+        let mut recaptures = MoveGen::new_legal(&taken);
+        recaptures.set_iterator_mask(BitBoard::from_square(capture.get_dest()));
+        recaptures.next().is_some()
+    }
+
+    /// Whether a legal move takes an enemy piece (en passant included)
+    fn is_capture(board: &Board, chess_move: ChessMove) -> bool {
+        let from = chess_move.get_source();
+        let to = chess_move.get_dest();
+        board.color_on(to) == Some(!board.side_to_move())
+            || (board.piece_on(from) == Some(Piece::Pawn) && from.get_file() != to.get_file())
+    }
+
+    /// Material of one side in centipawns
+    fn material(board: &Board, color: Color) -> i32 {
+        [Piece::Pawn, Piece::Knight, Piece::Bishop, Piece::Rook, Piece::Queen]
+            .iter()
+            .map(|piece| (board.pieces(*piece) & board.color_combined(color)).popcnt() as i32 * PIECE_VALUES[piece.to_index()])
+            .sum()
+    }
+
+    fn value(piece: Option<Piece>) -> i32 {
+        piece.map_or(0, |piece| PIECE_VALUES[piece.to_index()])
+    }
+
+    /// Number of king moves between two squares
+    fn distance(a: Square, b: Square) -> i32 {
+        let files = (a.get_file().to_index() as i32 - b.get_file().to_index() as i32).abs();
+        let ranks = (a.get_rank().to_index() as i32 - b.get_rank().to_index() as i32).abs();
+        files.max(ranks)
+    }
+}
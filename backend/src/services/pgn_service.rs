@@ -0,0 +1,427 @@
+use crate::database::{get_game_by_id, get_game_moves, get_user_by_id};
+use crate::models::{Game, GameMove, Personality, Termination};
+use crate::services::{ClockService, EngineRegistry};
+use sqlx::SqlitePool;
+
+/// PGN export lines are wrapped before this many characters
+const PGN_LINE_WIDTH: usize = 80;
+
+/// A single game read from a PGN file
+#[derive(Debug, Clone)]
+pub struct PgnGame {
+    /// Tag pairs in file order (e.g., ("White", "alice"))
+    pub tags: Vec<(String, String)>,
+    /// Mainline moves in SAN, without move numbers, comments or variations
+    pub moves: Vec<String>,
+    /// Game termination marker ("1-0", "0-1", "1/2-1/2" or "*")
+    pub result: String,
+}
+
+impl PgnGame {
+    /// Returns the value of a tag, if present
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Service responsible for converting games to and from Portable Game Notation
+pub struct PgnService;
+
+impl PgnService {
+    /// Exports a stored game as PGN
+    ///
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `game_id` - Unique identifier of the game
+    ///
+    /// # Returns
+    /// Result<Option<String>, String> - PGN text, None if the game doesn't exist
+    pub async fn export_game(pool: &SqlitePool, game_id: &str) -> Result<Option<String>, String> {
+        let game = match get_game_by_id(pool, game_id)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?
+        {
+            Some(game) => game,
+            None => return Ok(None),
+        };
+
+        let moves = get_game_moves(pool, game_id)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?;
+
+        let username = get_user_by_id(pool, &game.user_id)
+            .await
+            .map_err(|e| format!("Database error: {}", e))?
+            .map(|user| user.username)
+            .unwrap_or_else(|| "?".to_string());
+
+        Ok(Some(Self::to_pgn(&game, &moves, &username)))
+    }
+
+    /// Builds the PGN text of a game: Seven Tag Roster followed by the movetext
+    ///
+    /// # Arguments
+    /// * `game` - Game to export
+    /// * `moves` - Move history of the game, ordered by ply
+    /// * `username` - Name of the human player (White or Black depending on `player_color`)
+    pub fn to_pgn(game: &Game, moves: &[GameMove], username: &str) -> String {
+        let result = Self::result_tag(game.result.as_deref());
+        let engine_name = EngineRegistry::global().engine_for_game(game).display_name();
+        let engine = match game.personality {
+            Personality::Balanced => format!("{} (level {})", engine_name, game.difficulty),
+            personality => format!("{} (level {}, {})", engine_name, game.difficulty, personality.as_str()),
+        };
+        let (white, black) = if game.player_color == "black" {
+            (engine, username.to_string())
+        } else {
+            (username.to_string(), engine)
+        };
+
+        let tags = [
+            ("Event", "Casual game".to_string()),
+            ("Site", "?".to_string()),
+            ("Date", game.created_at.format("%Y.%m.%d").to_string()),
+            ("Round", "-".to_string()),
+            ("White", white),
+            ("Black", black),
+            ("Result", result.to_string()),
+        ];
+
+        let mut pgn = String::new();
+        for (name, value) in tags {
+            pgn.push_str(&format!("[{} \"{}\"]\n", name, Self::escape_tag(&value)));
+        }
+        if let Some(time_control) = ClockService::pgn_tag(game) {
+            pgn.push_str(&format!("[TimeControl \"{}\"]\n", time_control));
+        }
+        if let Some(termination) = game.termination {
+            pgn.push_str(&format!("[Termination \"{}\"]\n", Self::termination_tag(termination)));
+        }
+        if game.variant == "chess960" {
+            pgn.push_str("[Variant \"Chess960\"]\n");
+        }
+        if let Some(fen) = &game.start_fen {
+            pgn.push_str("[SetUp \"1\"]\n");
+            pgn.push_str(&format!("[FEN \"{}\"]\n", Self::escape_tag(fen)));
+        }
+        pgn.push('\n');
+        pgn.push_str(&Self::movetext(moves, result));
+        pgn.push('\n');
+        pgn
+    }
+
+    /// Maps `games.result` to a PGN result token
+    fn result_tag(result: Option<&str>) -> &'static str {
+        match result {
+            Some("white") => "1-0",
+            Some("black") => "0-1",
+            Some("draw") => "1/2-1/2",
+            _ => "*",
+        }
+    }
+
+    /// Maps a game termination to the standard PGN Termination tag values
+    fn termination_tag(termination: Termination) -> &'static str {
+        match termination {
+            Termination::Timeout => "time forfeit",
+            Termination::Aborted => "abandoned",
+            _ => "normal",
+        }
+    }
+
+    /// Formats the moves as numbered SAN, wrapped to PGN_LINE_WIDTH, ending with the result
+    fn movetext(moves: &[GameMove], result: &str) -> String {
+        let mut tokens = Vec::new();
+        for (index, game_move) in moves.iter().enumerate() {
+            // The full-move counter in the FEN is incremented after each Black move
+            let fullmove: u32 = game_move.fen_after.split_whitespace().nth(5)
+                .and_then(|n| n.parse().ok())
+                .unwrap_or(1);
+            let move_number = if game_move.side == "white" { fullmove } else { fullmove.saturating_sub(1).max(1) };
+            if game_move.side == "white" {
+                tokens.push(format!("{}.", move_number));
+            } else if index == 0 {
+                // Game starting with Black to move
+                tokens.push(format!("{}...", move_number));
+            }
+            tokens.push(game_move.san.clone());
+        }
+        tokens.push(result.to_string());
+
+        let mut text = String::new();
+        let mut line_len = 0;
+        for token in tokens {
+            if line_len > 0 && line_len + 1 + token.len() >= PGN_LINE_WIDTH {
+                text.push('\n');
+                line_len = 0;
+            } else if line_len > 0 {
+                text.push(' ');
+                line_len += 1;
+            }
+            line_len += token.len();
+            text.push_str(&token);
+        }
+        text
+    }
+
+    /// Escapes backslashes and quotes inside a PGN tag value
+    fn escape_tag(value: &str) -> String {
+        value.replace('\\', "\\\\").replace('"', "\\\"")
+    }
+
+    /// Parses a PGN file containing one or more games
+    ///
+    /// Comments, variations, NAGs and move numbers are skipped; only the mainline is kept.
+    ///
+    /// # Arguments
+    /// * `text` - PGN text
+    ///
+    /// # Returns
+    /// Result<Vec<PgnGame>, String> - Parsed games in file order or error message
+    pub fn parse_pgn(text: &str) -> Result<Vec<PgnGame>, String> {
+        let mut games = Vec::new();
+        let mut current = PgnGame { tags: Vec::new(), moves: Vec::new(), result: "*".to_string() };
+        let mut in_movetext = false;
+
+        let chars: Vec<char> = text.chars().collect();
+        let mut i = 0;
+        let mut line_start = true;
+
+        while i < chars.len() {
+            let c = chars[i];
+
+            // "%" in the first column escapes the whole line
+            if line_start && c == '%' {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+                continue;
+            }
+            line_start = c == '\n';
+
+            match c {
+                c if c.is_whitespace() => i += 1,
+                '[' => {
+                    // A tag section after movetext starts a new game (missing result marker)
+                    if in_movetext {
+                        games.push(std::mem::replace(
+                            &mut current,
+                            PgnGame { tags: Vec::new(), moves: Vec::new(), result: "*".to_string() },
+                        ));
+                        in_movetext = false;
+                    }
+                    let end = Self::find_tag_end(&chars, i)
+                        .ok_or_else(|| "Invalid PGN: unterminated tag".to_string())?;
+                    let tag: String = chars[i + 1..end].iter().collect();
+                    current.tags.push(Self::parse_tag(&tag)?);
+                    i = end + 1;
+                }
+                '{' => {
+                    while i < chars.len() && chars[i] != '}' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+                ';' => {
+                    while i < chars.len() && chars[i] != '\n' {
+                        i += 1;
+                    }
+                }
+                '(' => {
+                    // Skip the variation, including nested variations and comments
+                    let mut depth = 0;
+                    while i < chars.len() {
+                        match chars[i] {
+                            '(' => depth += 1,
+                            ')' => depth -= 1,
+                            '{' => {
+                                while i < chars.len() && chars[i] != '}' {
+                                    i += 1;
+                                }
+                            }
+                            _ => {}
+                        }
+                        i += 1;
+                        if depth == 0 {
+                            break;
+                        }
+                    }
+                }
+                ')' => return Err("Invalid PGN: unbalanced ')'".to_string()),
+                _ => {
+                    let start = i;
+                    while i < chars.len() && !chars[i].is_whitespace() && !"[]{}();".contains(chars[i]) {
+                        i += 1;
+                    }
+                    let token: String = chars[start..i].iter().collect();
+                    in_movetext = true;
+
+                    match token.as_str() {
+                        "1-0" | "0-1" | "1/2-1/2" | "*" => {
+                            current.result = token;
+                            games.push(std::mem::replace(
+                                &mut current,
+                                PgnGame { tags: Vec::new(), moves: Vec::new(), result: "*".to_string() },
+                            ));
+                            in_movetext = false;
+                        }
+                        t if t.starts_with('$') => {}
+                        // Bare move numbers ("12.") and ellipses ("...") carry no move
+                        t if t.chars().all(|ch| ch.is_ascii_digit() || ch == '.') => {}
+                        t => {
+                            // Move numbers ("12.", "12...") may be glued to the move ("12.e4")
+                            let digits = t.len() - t.trim_start_matches(|ch: char| ch.is_ascii_digit()).len();
+                            let san = if digits > 0 && t[digits..].starts_with('.') {
+                                t[digits..].trim_start_matches('.')
+                            } else {
+                                t
+                            };
+                            let san = san.trim_end_matches(['!', '?']);
+                            if !san.is_empty() {
+                                current.moves.push(san.to_string());
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        if in_movetext || !current.tags.is_empty() {
+            games.push(current);
+        }
+
+        if games.is_empty() {
+            return Err("Invalid PGN: no game found".to_string());
+        }
+        Ok(games)
+    }
+
+    /// Finds the closing bracket of a tag pair, ignoring brackets inside the quoted value
+    fn find_tag_end(chars: &[char], start: usize) -> Option<usize> {
+        let mut in_string = false;
+        let mut i = start + 1;
+        while i < chars.len() {
+            match chars[i] {
+                '\\' if in_string => i += 1,
+                '"' => in_string = !in_string,
+                ']' if !in_string => return Some(i),
+                _ => {}
+            }
+            i += 1;
+        }
+        None
+    }
+
+    /// Parses the inside of a tag pair: `Name "value"`
+    fn parse_tag(tag: &str) -> Result<(String, String), String> {
+        let tag = tag.trim();
+        let (name, rest) = tag
+            .split_once(char::is_whitespace)
+            .ok_or_else(|| format!("Invalid PGN tag: [{}]", tag))?;
+        let value = rest
+            .trim()
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .ok_or_else(|| format!("Invalid PGN tag: [{}]", tag))?;
+        Ok((name.to_string(), value.replace("\\\"", "\"").replace("\\\\", "\\")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use crate::services::chess_service::STARTING_FEN;
+    use crate::services::{ChessService, GameService};
+
+    /// Plays SAN moves in a new game, recording them like the game service does
+    fn played_game(start_fen: Option<&str>, sans: &[&str], result: Option<&str>) -> (Game, Vec<GameMove>) {
+        let mut game = GameService::new_game("user", 5, start_fen.map(str::to_string), "white".to_string(), "standard".to_string());
+        let mut moves = Vec::new();
+        for san in sans {
+            let details = ChessService::describe_move(&game.fen, san).unwrap();
+            game.fen = ChessService::make_move(&game.fen, san).unwrap();
+            game.moves_count += 1;
+            moves.push(GameMove {
+                id: format!("move-{}", game.moves_count),
+                game_id: game.id.clone(),
+                ply: game.moves_count,
+                side: details.color,
+                uci: details.uci,
+                san: details.san,
+                fen_after: game.fen.clone(),
+                think_time_ms: None,
+                created_at: Utc::now(),
+            });
+        }
+        game.result = result.map(str::to_string);
+        (game, moves)
+    }
+
+    fn round_trip(game: &Game, moves: &[GameMove]) -> PgnGame {
+        let pgn = PgnService::to_pgn(game, moves, "alice \"the\" player");
+        let mut games = PgnService::parse_pgn(&pgn).unwrap();
+        assert_eq!(games.len(), 1, "{}", pgn);
+        games.remove(0)
+    }
+
+    #[test]
+    fn exported_game_reads_back() {
+        // Long enough for the movetext to wrap
+        let sans = [
+            "e4", "e5", "Nf3", "Nc6", "Bb5", "a6", "Ba4", "Nf6", "O-O", "Be7", "Re1", "b5",
+            "Bb3", "d6", "c3", "O-O", "h3", "Nb8", "d4", "Nbd7", "Nbd2", "Bb7", "Bc2", "Re8",
+        ];
+        let (game, moves) = played_game(None, &sans, Some("draw"));
+        let parsed = round_trip(&game, &moves);
+
+        assert_eq!(parsed.moves, sans);
+        assert_eq!(parsed.result, "1/2-1/2");
+        assert_eq!(parsed.tag("White"), Some("alice \"the\" player"));
+        assert_eq!(parsed.tag("Result"), Some("1/2-1/2"));
+        assert_eq!(parsed.tag("FEN"), None);
+    }
+
+    #[test]
+    fn custom_position_with_black_to_move_reads_back() {
+        let start = "4k3/P7/8/8/8/8/8/4K2R b K - 0 30";
+        let (game, moves) = played_game(Some(start), &["Kd7", "a8=Q", "Kc7", "O-O"], None);
+        let parsed = round_trip(&game, &moves);
+
+        assert_eq!(parsed.moves, ["Kd7", "a8=Q", "Kc7", "O-O"]);
+        assert_eq!(parsed.result, "*");
+        assert_eq!(parsed.tag("SetUp"), Some("1"));
+        assert_eq!(parsed.tag("FEN"), Some(start));
+
+        // The moves replay to the same position
+        let mut fen = start.to_string();
+        for san in &parsed.moves {
+            fen = ChessService::make_move(&fen, san).unwrap();
+        }
+        assert_eq!(fen, game.fen);
+    }
+
+    #[test]
+    fn detached_move_numbers_and_ellipses_are_skipped() {
+        let games = PgnService::parse_pgn("1. e4 e5 2. Nf3 2... Nc6 3. Bb5 3. ... a6 4 . Ba4 ... Nf6 *").unwrap();
+        let sans = ["e4", "e5", "Nf3", "Nc6", "Bb5", "a6", "Ba4", "Nf6"];
+        assert_eq!(games[0].moves, sans);
+
+        let mut fen = STARTING_FEN.to_string();
+        for san in &games[0].moves {
+            fen = ChessService::make_move(&fen, san).unwrap();
+        }
+        assert!(fen.ends_with(" w KQkq - 2 5"), "{}", fen);
+
+        // Black to move from a custom position, as exported with "12. ..."
+        let start = "4k3/8/8/3q4/8/8/8/4K3 b - - 0 12";
+        let text = format!("[SetUp \"1\"]\n[FEN \"{}\"]\n\n12. ... Qd7 13. Kf1 *", start);
+        let games = PgnService::parse_pgn(&text).unwrap();
+        assert_eq!(games[0].moves, ["Qd7", "Kf1"]);
+        let fen = ChessService::make_move(start, "Qd7").unwrap();
+        ChessService::make_move(&fen, "Kf1").unwrap();
+    }
+}
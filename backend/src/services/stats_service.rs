@@ -1,6 +1,6 @@
 use sqlx::SqlitePool;
 use uuid::Uuid;
-use crate::models::{User, UserRecord, UserLevelStats, UserProfile};
+use crate::models::{User, UserRecord, UserLevelStats, UserModeStats, UserProfile};
 
 /// Service responsible for managing user statistics and records
 pub struct StatsService;
@@ -15,6 +15,7 @@ impl StatsService {
     /// * `duration_seconds` - Total game duration in seconds
     /// * `moves_count` - Total moves made in the game
     /// * `won` - Whether the user won the game
+    /// * `record_eligible` - Whether a win may set a personal record (false for imported games)
     /// 
     /// # Updates
     /// - User's total play time and streaks
@@ -27,6 +28,7 @@ impl StatsService {
         duration_seconds: i32,
         moves_count: i32,
         won: bool,
+        record_eligible: bool,
     ) -> Result<(), sqlx::Error> {
         println!("📊 Updating stats for user {} - Level {}, Time: {}s, Moves: {}, Won: {}",
                  user_id, difficulty, duration_seconds, moves_count, won);
@@ -86,7 +88,7 @@ impl StatsService {
         .await?;
 
         // Update personal record if this was a win
-        if won {
+        if won && record_eligible {
             Self::update_personal_record(&mut tx, user_id, difficulty, duration_seconds, moves_count).await?;
         }
 
@@ -95,6 +97,58 @@ impl StatsService {
         Ok(())
     }
 
+    /// Updates statistics for a game played outside the standard mode
+    /// 
+    /// # Arguments
+    /// * `pool` - Database connection pool
+    /// * `user_id` - User identifier
+    /// * `mode` - Game mode (e.g., "custom_position")
+    /// * `difficulty` - Game difficulty level (1-20)
+    /// * `duration_seconds` - Total game duration in seconds
+    /// * `moves_count` - Total moves made in the game
+    /// * `won` - Whether the user won the game
+    /// 
+    /// These games don't touch the user's totals, streaks, level statistics or records.
+    pub async fn update_mode_stats(
+        pool: &SqlitePool,
+        user_id: &str,
+        mode: &str,
+        difficulty: i32,
+        duration_seconds: i32,
+        moves_count: i32,
+        won: bool,
+    ) -> Result<(), sqlx::Error> {
+        println!("📊 Updating {} stats for user {} - Level {}, Time: {}s, Moves: {}, Won: {}",
+                 mode, user_id, difficulty, duration_seconds, moves_count, won);
+
+        let stats_id = Uuid::new_v4().to_string();
+        let games_won_val = if won { 1 } else { 0 };
+
+        sqlx::query!(
+            "INSERT INTO user_mode_stats (id, user_id, mode, difficulty, games_played, games_won, total_time_seconds, total_moves)
+             VALUES (?, ?, ?, ?, 1, ?, ?, ?)
+             ON CONFLICT(user_id, mode, difficulty) DO UPDATE SET
+                games_played = games_played + 1,
+                games_won = games_won + ?,
+                total_time_seconds = total_time_seconds + ?,
+                total_moves = total_moves + ?",
+            stats_id,
+            user_id,
+            mode,
+            difficulty,
+            games_won_val,
+            duration_seconds,
+            moves_count,
+            games_won_val,
+            duration_seconds,
+            moves_count
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
     /// Updates a user's personal record for a difficulty level
     /// 
     /// # Arguments
@@ -223,10 +277,31 @@ impl StatsService {
             average_moves: row.average_moves as i32,
         }).collect();
 
+        // Fetch statistics of other game modes
+        let mode_rows = sqlx::query!(
+            "SELECT id, user_id, mode, difficulty, games_played, games_won, total_time_seconds, total_moves
+             FROM user_mode_stats WHERE user_id = ? ORDER BY mode ASC, difficulty ASC",
+            user_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let mode_stats: Vec<UserModeStats> = mode_rows.into_iter().map(|row| UserModeStats {
+            id: row.id,
+            user_id: row.user_id,
+            mode: row.mode,
+            difficulty: row.difficulty as i32,
+            games_played: row.games_played as i32,
+            games_won: row.games_won as i32,
+            total_time_seconds: row.total_time_seconds as i32,
+            total_moves: row.total_moves as i32,
+        }).collect();
+
         Ok(UserProfile {
             user,
             records,
             level_stats,
+            mode_stats,
         })
     }
 
@@ -263,7 +338,7 @@ impl StatsService {
             let games_won = stat.games_won as f64;
             let games_played = stat.games_played as f64;
             let win_rate = games_won / games_played;
-            let level_elo = stat.difficulty as i64 * 100;
+            let level_elo = stat.difficulty * 100;
             
             // If win rate >= 50%, player can handle this difficulty level
             if win_rate >= 0.5 {
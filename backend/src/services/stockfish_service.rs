@@ -1,17 +1,77 @@
-use std::process::Stdio;
-use tokio::process::Command as TokioCommand;
 use tokio::io::{AsyncWriteExt, AsyncBufReadExt, BufReader as TokioBufReader};
 use rand::{Rng, SeedableRng};
 use rand::rngs::StdRng;
-use tracing::{debug, error, info, warn};
+use tracing::{debug, info, warn};
+use crate::models::{DifficultyProfile, Personality};
+use crate::services::{ChessService, PersonalityService};
+use crate::services::personality_service::PERSONALITY_MULTIPV;
+use crate::services::engine_pool::EnginePool;
 use tokio::time::{Duration, Instant};
 
+/// Longest time an analysis may take before the engine is told to stop
+const ANALYSIS_MAX_MS: u64 = 10_000;
+
+/// Centipawn value standing for a mate on the board (mates in fewer moves score higher)
+const MATE_CP: i32 = 100_000;
+
+/// Score of a position as reported by the engine, from the side to move's point of view
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EngineScore {
+    /// Centipawns
+    Cp(i32),
+    /// Moves until mate (negative when the side to move gets mated)
+    Mate(i32),
+}
+
+impl EngineScore {
+    /// Centipawn value usable for comparisons, mates being worth more than any material
+    pub fn as_cp(self) -> i32 {
+        match self {
+            EngineScore::Cp(cp) => cp,
+            EngineScore::Mate(moves) if moves > 0 => MATE_CP - moves,
+            EngineScore::Mate(moves) => -MATE_CP - moves,
+        }
+    }
+
+    /// Same score from White's point of view, given the position it was computed for
+    pub fn for_white(self, fen: &str) -> EngineScore {
+        if fen.split_whitespace().nth(1) != Some("b") {
+            return self;
+        }
+        match self {
+            EngineScore::Cp(cp) => EngineScore::Cp(-cp),
+            EngineScore::Mate(moves) => EngineScore::Mate(-moves),
+        }
+    }
+}
+
+/// One principal variation reported by the engine
+#[derive(Debug, Clone)]
+pub struct EngineLine {
+    /// 1 for the best line, 2 for the second best... (MultiPV)
+    pub multipv: u32,
+    pub depth: i32,
+    pub score: EngineScore,
+    /// Moves in coordinate notation, starting with the line's first move
+    pub pv: Vec<String>,
+}
+
+/// Result of a full-strength search
+#[derive(Debug, Clone)]
+pub struct EngineAnalysis {
+    pub best_move: Option<String>,
+    /// Deepest line reported for each MultiPV index, best line first
+    pub lines: Vec<EngineLine>,
+}
+
 /// Service for interfacing with Stockfish chess engine via UCI protocol
 pub struct StockfishService;
 
 impl StockfishService {
     /// Returns the path/command to Stockfish (env override then common paths)
-    fn get_stockfish_command() -> String {
+    /// 
+    /// Probing runs each candidate binary, so this is only called once, by `EnginePool::global`.
+    pub(crate) fn get_stockfish_command() -> String {
         if let Ok(cmd) = std::env::var("STOCKFISH_PATH") {
             if std::process::Command::new(&cmd).arg("--help").output().is_ok() {
                 info!("Using Stockfish from env: {}", cmd);
@@ -32,120 +92,86 @@ impl StockfishService {
                 return path.to_string();
             }
         }
-        warn!("Stockfish not found, using default command name");
+        warn!("Stockfish not found, moves will come from the built-in engine");
         "stockfish".to_string()
     }
 
-    /// Returns the best move for a FEN at a given difficulty (with intentional weakness)
-    pub async fn get_best_move(fen: &str, difficulty: i32) -> Result<String, String> {
-        let target_elo = difficulty * 100;
-        debug!("Analyze: level={} elo={} fen={}", difficulty, target_elo, fen);
-
-        let (skill_level, time_limit_ms, random_move_chance, blunder_chance) = match difficulty {
-            1 => (-20, 1, 0.95, 0.9),
-            2 => (-18, 1, 0.9, 0.8),
-            3 => (-15, 2, 0.85, 0.7),
-            4 => (-12, 5, 0.8, 0.6),
-            5 => (-10, 10, 0.75, 0.5),
-            6 => (-8, 25, 0.4, 0.3),
-            7 => (-5, 50, 0.35, 0.25),
-            8 => (-3, 75, 0.3, 0.2),
-            9 => (-1, 100, 0.25, 0.15),
-            10 => (0, 150, 0.2, 0.1),
-            11 => (2, 200, 0.15, 0.08),
-            12 => (4, 300, 0.12, 0.06),
-            13 => (6, 400, 0.1, 0.04),
-            14 => (8, 500, 0.08, 0.03),
-            15 => (10, 750, 0.06, 0.02),
-            16 => (12, 450, 0.04, 0.01),
-            17 => (14, 350, 0.02, 0.005),
-            18 => (16, 300, 0.01, 0.002),
-            19 => (18, 250, 0.005, 0.0),
-            20 => (20, 200, 0.003, 0.0),
-            _ => (20, 200, 0.0, 0.0),
-        };
-
+    /// Returns the best move for a FEN with the weaknesses of a difficulty profile
+    ///
+    /// Every random choice comes from `seed`: the same seed, position and engine output
+    /// give the same move. A personality then picks its favourite among the candidates
+    /// about as good as Stockfish's move, before the profile's weaknesses apply.
+    pub async fn get_best_move(
+        fen: &str,
+        profile: &DifficultyProfile,
+        personality: Personality,
+        seed: u64,
+    ) -> Result<String, String> {
         debug!(
-            "Settings: skill={} time={}ms random={:.1}% blunder={:.1}%",
-            skill_level,
-            time_limit_ms,
-            random_move_chance * 100.0,
-            blunder_chance * 100.0
+            "Analyze: profile={} level={} skill={} time={}ms elo={:?} fen={}",
+            profile.name, profile.level, profile.skill_level, profile.movetime_ms, profile.uci_elo, fen
         );
 
-        if difficulty <= 5 {
-            let mut rng = StdRng::from_entropy();
+        let mut rng = StdRng::seed_from_u64(seed);
+        if profile.random_move_chance > 0.0 {
             let random_roll = rng.gen::<f64>();
-            debug!("Random roll: level={} roll={:.3} threshold={:.3}", difficulty, random_roll, random_move_chance);
-            if random_roll < random_move_chance {
+            debug!("Random roll: profile={} roll={:.3} threshold={:.3}", profile.name, random_roll, profile.random_move_chance);
+            if random_roll < profile.random_move_chance {
                 debug!("Generating random legal move (low level)");
-                match Self::get_random_legal_move(fen).await {
+                match Self::get_random_legal_move(fen, &mut rng) {
                     Ok(random_move) => return Ok(random_move),
                     Err(e) => warn!("Random move generation failed: {}", e),
                 }
             }
         }
 
-        let stockfish_move = Self::get_stockfish_move_with_weakness(
-            fen,
-            skill_level,
-            time_limit_ms,
-            random_move_chance,
-            blunder_chance,
-            difficulty,
-        )
-        .await?;
+        let stockfish_move = Self::get_stockfish_move_with_weakness(fen, profile, personality, &mut rng).await?;
 
         Ok(stockfish_move)
     }
 
     /// Generates a completely random legal move using the chess crate (low levels)
-    async fn get_random_legal_move(fen: &str) -> Result<String, String> {
-        use chess::{Board, MoveGen};
-        use std::str::FromStr;
-        let board = Board::from_str(fen).map_err(|e| format!("Invalid FEN: {}", e))?;
-        let movegen = MoveGen::new_legal(&board);
-        let legal_moves: Vec<String> = movegen.map(|m| m.to_string()).collect();
+    fn get_random_legal_move(fen: &str, rng: &mut StdRng) -> Result<String, String> {
+        let legal_moves = ChessService::get_legal_moves(fen)?;
         debug!("{} legal moves available for random selection", legal_moves.len());
         if legal_moves.is_empty() {
             return Err("No legal moves found".to_string());
         }
-        let mut rng = StdRng::from_entropy();
         let chosen_move = legal_moves[rng.gen_range(0..legal_moves.len())].clone();
         Ok(chosen_move)
     }
 
-    /// Computes a move with Stockfish then applies weakness according to difficulty
+    /// Computes a move with Stockfish then applies the profile's weaknesses
     async fn get_stockfish_move_with_weakness(
         fen: &str,
-        skill_level: i32,
-        time_limit_ms: i32,
-        random_move_chance: f64,
-        blunder_chance: f64,
-        difficulty: i32,
+        profile: &DifficultyProfile,
+        personality: Personality,
+        rng: &mut StdRng,
     ) -> Result<String, String> {
-        let stockfish_cmd = Self::get_stockfish_command();
-        let mut child = TokioCommand::new(&stockfish_cmd)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()
-            .map_err(|e| format!("Failed to start Stockfish: {}", e))?;
-
-        let mut stdin = child.stdin.take().ok_or("Failed to open stdin")?;
-        let stdout = child.stdout.take().ok_or("Failed to open stdout")?;
-
-        // Initialize UCI and configure options
-        Self::uci_init(&mut stdin).await?;
-        Self::configure_engine(&mut stdin, skill_level, difficulty).await?;
-        Self::set_position_and_go(&mut stdin, fen, time_limit_ms).await?;
-
-        let mut reader = TokioBufReader::new(stdout);
-        let (best, all_moves, bad_moves) = Self::collect_moves_with_timeout(
-            &mut reader,
-            time_limit_ms,
+        let mut engine = EnginePool::global().checkout(fen).await?;
+
+        // A personality needs a few candidates to choose from
+        let multipv = match personality {
+            Personality::Balanced => profile.multipv,
+            _ => profile.multipv.max(PERSONALITY_MULTIPV),
+        };
+        // Configure options for this profile (the process may have served another one before)
+        Self::configure_engine(&mut engine.stdin, profile, multipv, ChessService::is_chess960_fen(fen)).await?;
+        Self::set_position_and_go(&mut engine.stdin, fen, profile.movetime_ms).await?;
+
+        let (best, lines) = Self::collect_moves_with_timeout(
+            &mut engine.reader,
+            profile.movetime_ms,
         )
         .await?;
+        engine.release().await;
+
+        let all_moves: Vec<(String, i32)> = lines.iter().map(|line| (line.pv[0].clone(), line.score.as_cp())).collect();
+        let bad_moves: Vec<String> = all_moves
+            .iter()
+            .filter(|(_, score)| *score < -200)
+            .map(|(mv, _)| mv.clone())
+            .collect();
 
         // If engine provided no explicit bestmove (timeout), fallback to best from list or random
         let best_move = best.or_else(|| {
@@ -156,141 +182,186 @@ impl StockfishService {
         });
 
         let final_move = if let Some(bm) = best_move {
-            Self::apply_weakness(
-                bm,
-                &all_moves,
-                &bad_moves,
-                random_move_chance,
-                blunder_chance,
-                difficulty,
-            )
+            let bm = PersonalityService::pick_move(fen, &lines, &bm, personality);
+            Self::apply_weakness(bm, &all_moves, &bad_moves, profile, rng)
         } else {
             // Last resort: generate a random legal move quickly
-            match Self::get_random_legal_move(fen).await {
-                Ok(mv) => mv,
-                Err(_) => {
-                    // If everything fails, terminate and error
-                    let _ = stdin.write_all(b"quit\n").await;
-                    let _ = child.kill().await;
-                    return Err("Engine timeout without moves".to_string());
-                }
-            }
+            Self::get_random_legal_move(fen, rng)
+                .map_err(|_| "Engine timeout without moves".to_string())?
         };
 
-        // Shutdown engine
-        let _ = stdin.write_all(b"quit\n").await;
-        let _ = child.wait().await;
-        debug!("Final move: {} (level={} elo={})", final_move, difficulty, difficulty * 100);
+        debug!("Final move: {} (profile={})", final_move, profile.name);
         Ok(final_move)
     }
 
     /// Applies weakness to move selection (kept separate for testability)
+    /// 
+    /// Each kind of weaker move is tried in turn with the profile's chance for it.
     fn apply_weakness(
         best_move: String,
         all_moves: &[(String, i32)],
         bad_moves: &[String],
-        random_chance: f64,
-        blunder_chance: f64,
-        difficulty: i32,
+        profile: &DifficultyProfile,
+        rng: &mut StdRng,
     ) -> String {
-        let mut rng = StdRng::from_entropy();
-        if difficulty <= 3 && !bad_moves.is_empty() && rng.gen::<f64>() < blunder_chance {
+        if !bad_moves.is_empty() && rng.gen::<f64>() < profile.blunder_chance {
             return bad_moves[rng.gen_range(0..bad_moves.len())].clone();
         }
-        if difficulty <= 5 && !all_moves.is_empty() && rng.gen::<f64>() < random_chance {
+        if !all_moves.is_empty() && rng.gen::<f64>() < profile.any_candidate_chance {
             return all_moves[rng.gen_range(0..all_moves.len())].0.clone();
         }
-        if difficulty <= 10 && !all_moves.is_empty() && rng.gen::<f64>() < random_chance {
+        if !all_moves.is_empty() && rng.gen::<f64>() < profile.weak_candidate_chance {
             let bottom_start = (all_moves.len() as f64 * 0.3) as usize;
             if bottom_start < all_moves.len() {
                 return all_moves[rng.gen_range(bottom_start..all_moves.len())].0.clone();
             }
         }
-        if difficulty <= 15 && all_moves.len() > 3 && rng.gen::<f64>() < random_chance {
+        if all_moves.len() > 3 && rng.gen::<f64>() < profile.near_best_chance {
             let range_end = (all_moves.len() as f64 * 0.6).max(3.0) as usize;
             return all_moves[rng.gen_range(1..range_end.min(all_moves.len()))].0.clone();
         }
         best_move
     }
 
-    /// Extracts centipawn score from a single Stockfish info line
-    fn extract_score(line: &str) -> Option<i32> {
-        if let Some(cp_pos) = line.find("score cp ") {
-            let score_part = &line[cp_pos + 9..];
-            if let Some(score_str) = score_part.split_whitespace().next() {
-                return score_str.parse().ok();
-            }
+    /// Extracts the score (centipawns or mate) from a single Stockfish info line
+    fn extract_score(line: &str) -> Option<EngineScore> {
+        let mut tokens = line.split_whitespace().skip_while(|t| *t != "score").skip(1);
+        match (tokens.next(), tokens.next().and_then(|v| v.parse().ok())) {
+            (Some("cp"), Some(cp)) => Some(EngineScore::Cp(cp)),
+            (Some("mate"), Some(moves)) => Some(EngineScore::Mate(moves)),
+            _ => None,
         }
-        None
     }
 
-    /// Evaluates a position (centipawn score) with a limited depth
-    pub async fn evaluate_position(fen: &str, depth: i32) -> Result<i32, String> {
+    /// Evaluates a position (score for the side to move) with a limited depth
+    pub async fn evaluate_position(fen: &str, depth: i32) -> Result<EngineScore, String> {
         debug!("Evaluate fen={} depth={}", fen, depth);
-        let stockfish_cmd = Self::get_stockfish_command();
-        let mut child = TokioCommand::new(&stockfish_cmd)
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .spawn()
-            .map_err(|e| format!("Failed to start Stockfish: {}", e))?;
-        let mut stdin = child.stdin.take().ok_or("Failed to open stdin")?;
-        let stdout = child.stdout.take().ok_or("Failed to open stdout")?;
-        // UCI init & position
-        Self::uci_init(&mut stdin).await?;
-        stdin
-            .write_all(format!("position fen {}\n", fen).as_bytes())
-            .await
-            .map_err(|e| format!("Failed to write position: {}", e))?;
-        stdin
-            .write_all(format!("go depth {}\n", depth).as_bytes())
+        let analysis = Self::analyse(fen, Some(depth), None, 1).await?;
+        Ok(analysis.lines.first().map_or(EngineScore::Cp(0), |line| line.score))
+    }
+
+    /// Runs a full-strength search (no skill limit) on a position
+    ///
+    /// # Arguments
+    /// * `fen` - Position to analyse
+    /// * `depth` - Maximum search depth (None: no depth limit)
+    /// * `movetime_ms` - Maximum search time (None: no time limit)
+    /// * `multipv` - Number of lines to report
+    ///
+    /// # Returns
+    /// Result<EngineAnalysis, String> - Best move and lines, scores for the side to move
+    pub async fn analyse(
+        fen: &str,
+        depth: Option<i32>,
+        movetime_ms: Option<i32>,
+        multipv: u32,
+    ) -> Result<EngineAnalysis, String> {
+        debug!("Analyse fen={} depth={:?} movetime={:?} multipv={}", fen, depth, movetime_ms, multipv);
+        let mut engine = EnginePool::global().checkout(fen).await?;
+
+        // Full strength: undo any weakening left by an earlier game search
+        let mut options = String::from("setoption name Skill Level value 20\nsetoption name UCI_LimitStrength value false\n");
+        options.push_str(&format!("setoption name MultiPV value {}\n", multipv.max(1)));
+        options.push_str(&format!("setoption name UCI_Chess960 value {}\n", ChessService::is_chess960_fen(fen)));
+        let mut go = String::from("go");
+        if let Some(depth) = depth {
+            go.push_str(&format!(" depth {}", depth));
+        }
+        if let Some(movetime) = movetime_ms {
+            go.push_str(&format!(" movetime {}", movetime));
+        }
+        if depth.is_none() && movetime_ms.is_none() {
+            go.push_str(" infinite");
+        }
+        engine
+            .stdin
+            .write_all(format!("{}isready\nposition fen {}\n{}\n", options, fen, go).as_bytes())
             .await
             .map_err(|e| format!("Failed to write go: {}", e))?;
 
-        let mut reader = TokioBufReader::new(stdout);
+        let mut max = Duration::from_millis(movetime_ms.map_or(ANALYSIS_MAX_MS, |ms| ms as u64 + 400).min(ANALYSIS_MAX_MS));
         let mut line = String::new();
-        let mut score = 0;
+        let mut lines: Vec<EngineLine> = Vec::new();
+        let mut best_move = None;
+        let mut stopped = false;
         let start = Instant::now();
-        let max = Duration::from_secs(2);
         loop {
             let remaining = max.checked_sub(start.elapsed()).unwrap_or(Duration::from_millis(0));
             if remaining.is_zero() {
-                warn!("Evaluation timeout");
-                break;
+                if stopped {
+                    warn!("Analysis timeout");
+                    break;
+                }
+                // Ask for the result of the search so far
+                let _ = engine.send("stop").await;
+                stopped = true;
+                max += Duration::from_millis(500);
+                continue;
             }
-            match tokio::time::timeout(remaining, reader.read_line(&mut line)).await {
+            match tokio::time::timeout(remaining, engine.reader.read_line(&mut line)).await {
                 Ok(Ok(n)) if n > 0 => {
-                    if let Some(cp) = Self::extract_score(&line) { score = cp; }
-                    if line.starts_with("bestmove") { break; }
+                    if let Some(info) = Self::parse_info_line(&line) {
+                        lines.retain(|l| l.multipv != info.multipv);
+                        lines.push(info);
+                    }
+                    if line.starts_with("bestmove") {
+                        best_move = line.split_whitespace().nth(1).filter(|m| *m != "(none)").map(|m| m.to_string());
+                        break;
+                    }
                     line.clear();
                 }
                 Ok(Ok(_)) => break,
-                Ok(Err(e)) => return Err(format!("Failed to read: {}", e)),
-                Err(_) => { warn!("Evaluation read timeout"); break; }
+                Ok(Err(e)) => return Err(format!("Failed to read from Stockfish: {}", e)),
+                Err(_) => continue,
             }
         }
-        let _ = stdin.write_all(b"quit\n").await;
-        let _ = child.wait().await;
-        Ok(score)
+        engine.release().await;
+
+        lines.sort_by_key(|l| l.multipv);
+        Ok(EngineAnalysis { best_move, lines })
     }
 
-    // --- helpers (no verbose comments inline) ---
-    async fn uci_init(stdin: &mut tokio::process::ChildStdin) -> Result<(), String> {
-        stdin.write_all(b"uci\n").await.map_err(|e| format!("Failed to write UCI: {}", e))?;
-        stdin.write_all(b"isready\n").await.map_err(|e| format!("Failed to write isready: {}", e))?;
-        Ok(())
+    /// Parses an "info" line carrying a principal variation (bound scores are skipped)
+    fn parse_info_line(line: &str) -> Option<EngineLine> {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        if tokens.first() != Some(&"info") || tokens.contains(&"lowerbound") || tokens.contains(&"upperbound") {
+            return None;
+        }
+        let value_after = |key: &str| tokens.iter().position(|t| *t == key).and_then(|i| tokens.get(i + 1));
+
+        let score = Self::extract_score(line)?;
+        let pv_start = tokens.iter().position(|t| *t == "pv")? + 1;
+        let pv: Vec<String> = tokens[pv_start..].iter().map(|m| m.to_string()).collect();
+        if pv.is_empty() {
+            return None;
+        }
+
+        Some(EngineLine {
+            multipv: value_after("multipv").and_then(|v| v.parse().ok()).unwrap_or(1),
+            depth: value_after("depth").and_then(|v| v.parse().ok()).unwrap_or(0),
+            score,
+            pv,
+        })
     }
 
+    // --- helpers (no verbose comments inline) ---
     async fn configure_engine(
         stdin: &mut tokio::process::ChildStdin,
-        skill_level: i32,
-        difficulty: i32,
+        profile: &DifficultyProfile,
+        multipv: i32,
+        chess960: bool,
     ) -> Result<(), String> {
+        // Chess960 positions carry Shredder-FEN castling rights and castling is king-takes-rook
         stdin
-            .write_all(format!("setoption name Skill Level value {}\n", skill_level).as_bytes())
+            .write_all(format!("setoption name UCI_Chess960 value {}\n", chess960).as_bytes())
+            .await
+            .map_err(|e| format!("Failed to set Chess960: {}", e))?;
+        stdin
+            .write_all(format!("setoption name Skill Level value {}\n", profile.skill_level).as_bytes())
             .await
             .map_err(|e| format!("Failed to set skill: {}", e))?;
-        if difficulty <= 10 {
-            let target_elo = (difficulty * 100).max(100);
+        // Pooled processes keep their options: every option is set for each search
+        if let Some(target_elo) = profile.uci_elo {
             stdin
                 .write_all(b"setoption name UCI_LimitStrength value true\n")
                 .await
@@ -299,13 +370,16 @@ impl StockfishService {
                 .write_all(format!("setoption name UCI_Elo value {}\n", target_elo).as_bytes())
                 .await
                 .map_err(|e| format!("Failed to set ELO: {}", e))?;
-        }
-        if difficulty <= 15 {
+        } else {
             stdin
-                .write_all(b"setoption name MultiPV value 20\n")
+                .write_all(b"setoption name UCI_LimitStrength value false\n")
                 .await
-                .map_err(|e| format!("Failed to set MultiPV: {}", e))?;
+                .map_err(|e| format!("Failed to set limit strength: {}", e))?;
         }
+        stdin
+            .write_all(format!("setoption name MultiPV value {}\n", multipv).as_bytes())
+            .await
+            .map_err(|e| format!("Failed to set MultiPV: {}", e))?;
         stdin.write_all(b"isready\n").await.map_err(|e| format!("Failed to write isready: {}", e))?;
         Ok(())
     }
@@ -329,10 +403,10 @@ impl StockfishService {
     async fn collect_moves_with_timeout(
         reader: &mut TokioBufReader<tokio::process::ChildStdout>,
         time_limit_ms: i32,
-    ) -> Result<(Option<String>, Vec<(String, i32)>, Vec<String>), String> {
+    ) -> Result<(Option<String>, Vec<EngineLine>), String> {
         let mut line = String::new();
-        let mut all_moves: Vec<(String, i32)> = Vec::new();
-        let mut bad_moves: Vec<String> = Vec::new();
+        // Latest line of each MultiPV index (earlier depths are superseded)
+        let mut lines: Vec<EngineLine> = Vec::new();
         let mut best: Option<String> = None;
         let start = Instant::now();
         let max = Duration::from_millis(time_limit_ms as u64 + 400);
@@ -344,17 +418,9 @@ impl StockfishService {
             }
             match tokio::time::timeout(remaining, reader.read_line(&mut line)).await {
                 Ok(Ok(n)) if n > 0 => {
-                    if line.contains("multipv") && line.contains("pv") {
-                        if let Some(pv_pos) = line.find("pv ") {
-                            let move_part = &line[pv_pos + 3..];
-                            if let Some(mv) = move_part.split_whitespace().next() {
-                                if mv.len() >= 4 {
-                                    let score = Self::extract_score(&line).unwrap_or(0);
-                                    all_moves.push((mv.to_string(), score));
-                                    if score < -200 { bad_moves.push(mv.to_string()); }
-                                }
-                            }
-                        }
+                    if let Some(info) = Self::parse_info_line(&line).filter(|info| info.pv[0].len() >= 4) {
+                        lines.retain(|l| l.multipv != info.multipv);
+                        lines.push(info);
                     }
                     if line.starts_with("bestmove") {
                         let parts: Vec<&str> = line.split_whitespace().collect();
@@ -368,6 +434,7 @@ impl StockfishService {
                 Err(_) => { warn!("Per-line read timeout"); break; }
             }
         }
-        Ok((best, all_moves, bad_moves))
+        lines.sort_by_key(|l| l.multipv);
+        Ok((best, lines))
     }
 }
\ No newline at end of file
@@ -0,0 +1,557 @@
+//! Engine integration tests, played against the scriptable fake engine (src/bin/fake_uci.rs)
+//!
+//! Every test writes the fake engine's script, then asks for a move the way a game does.
+//! The engine pool is global, so the tests take turns and share one runtime (pooled
+//! processes belong to the runtime that started them).
+
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use std::time::{Duration, Instant};
+use chrono::Utc;
+use sqlx::sqlite::SqlitePoolOptions;
+use sqlx::SqlitePool;
+use tokio::runtime::Runtime;
+use chess_backend::database::{create_user, get_game_by_id, get_game_moves};
+use chess_backend::models::{DifficultyProfile, DifficultyProfileInput, ImportPgnInput, MakeMoveInput, NewGameInput, Personality, Termination};
+use chess_backend::services::chess_service::STARTING_FEN;
+use chess_backend::services::engines::DEFAULT_ENGINE;
+use chess_backend::services::{ChessService, DifficultyProfiles, DifficultyService, GameService, StockfishService, UserService};
+
+/// Position after 1. e4, Black to move
+const AFTER_E4: &str = "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1";
+
+/// Settings shared by every test: the fake engine plays as Stockfish, from one process
+fn setup() -> MutexGuard<'static, ()> {
+    static LOCK: Mutex<()> = Mutex::new(());
+    static ENV: OnceLock<()> = OnceLock::new();
+    let guard = LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    ENV.get_or_init(|| {
+        std::env::set_var("STOCKFISH_PATH", env!("CARGO_BIN_EXE_fake_uci"));
+        std::env::set_var("STOCKFISH_POOL_SIZE", "1");
+        std::env::set_var("FAKE_UCI_SCRIPT", script_path());
+    });
+    guard
+}
+
+fn runtime() -> &'static Runtime {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+    RUNTIME.get_or_init(|| Runtime::new().expect("tokio runtime"))
+}
+
+fn script_path() -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("fake_uci_{}.txt", std::process::id()))
+}
+
+fn script(lines: &[&str]) {
+    std::fs::write(script_path(), lines.join("\n")).expect("write fake engine script");
+}
+
+/// Full strength: one candidate, no random or weaker moves
+fn profile() -> DifficultyProfile {
+    DifficultyProfile {
+        name: "test".to_string(),
+        level: 20,
+        skill_level: 20,
+        movetime_ms: 100,
+        uci_elo: None,
+        multipv: 1,
+        random_move_chance: 0.0,
+        blunder_chance: 0.0,
+        any_candidate_chance: 0.0,
+        weak_candidate_chance: 0.0,
+        near_best_chance: 0.0,
+        updated_at: Utc::now(),
+    }
+}
+
+fn best_move(fen: &str) -> Result<String, String> {
+    best_move_as(fen, Personality::Balanced)
+}
+
+fn best_move_as(fen: &str, personality: Personality) -> Result<String, String> {
+    runtime().block_on(StockfishService::get_best_move(fen, &profile(), personality, 42))
+}
+
+#[test]
+fn plays_the_scripted_bestmove() {
+    let _guard = setup();
+    script(&[
+        "info depth 1 multipv 1 score cp 20 pv d2d4",
+        "bestmove e2e4 ponder e7e5",
+    ]);
+    assert_eq!(best_move(STARTING_FEN).unwrap(), "e2e4");
+}
+
+#[test]
+fn timeout_falls_back_to_best_scored_candidate() {
+    let _guard = setup();
+    script(&[
+        "info depth 8 multipv 1 score cp 15 pv g1f3 d7d5",
+        "info depth 8 multipv 2 score cp 40 pv d2d4 d7d5",
+        "sleep 3000",
+        "bestmove g1f3",
+    ]);
+    let start = Instant::now();
+    assert_eq!(best_move(STARTING_FEN).unwrap(), "d2d4");
+    assert!(start.elapsed() < Duration::from_secs(2), "waited {:?}", start.elapsed());
+    // The engine was stopped and is still usable
+    script(&["bestmove b1c3"]);
+    assert_eq!(best_move(STARTING_FEN).unwrap(), "b1c3");
+}
+
+#[test]
+fn missing_bestmove_falls_back_to_best_scored_candidate() {
+    let _guard = setup();
+    script(&[
+        "info depth 5 multipv 1 score cp -30 pv b8c6",
+        "info depth 5 multipv 2 score mate 3 pv d8h4",
+        "info depth 5 multipv 3 score cp 10 pv e7e5",
+    ]);
+    assert_eq!(best_move(AFTER_E4).unwrap(), "d8h4");
+}
+
+#[test]
+fn silent_engine_falls_back_to_a_legal_move() {
+    let _guard = setup();
+    script(&[]);
+    let played = best_move(AFTER_E4).unwrap();
+    assert!(ChessService::get_legal_moves(AFTER_E4).unwrap().contains(&played), "{}", played);
+}
+
+#[test]
+fn crash_mid_search_keeps_the_candidates_and_restarts_the_engine() {
+    let _guard = setup();
+    script(&[
+        "info depth 3 multipv 1 score cp 25 pv c2c4",
+        "crash",
+    ]);
+    assert_eq!(best_move(STARTING_FEN).unwrap(), "c2c4");
+    script(&["bestmove g2g3"]);
+    assert_eq!(best_move(STARTING_FEN).unwrap(), "g2g3");
+}
+
+#[test]
+fn crash_on_go_falls_back_to_a_legal_move() {
+    let _guard = setup();
+    script(&["crash"]);
+    let played = best_move(STARTING_FEN).unwrap();
+    assert!(ChessService::get_legal_moves(STARTING_FEN).unwrap().contains(&played), "{}", played);
+    script(&["bestmove e2e4"]);
+    assert_eq!(best_move(STARTING_FEN).unwrap(), "e2e4");
+}
+
+#[test]
+fn hung_engine_is_replaced() {
+    let _guard = setup();
+    script(&[
+        "info depth 2 multipv 1 score cp 5 pv b1c3",
+        "hang",
+    ]);
+    assert_eq!(best_move(STARTING_FEN).unwrap(), "b1c3");
+    script(&["bestmove g1f3"]);
+    assert_eq!(best_move(STARTING_FEN).unwrap(), "g1f3");
+}
+
+#[test]
+fn garbage_output_is_ignored() {
+    let _guard = setup();
+    script(&[
+        "Stockfish 16 by the Stockfish developers",
+        "info string NNUE evaluation using nn-5af11540bbfe.nnue",
+        "%%% not uci at all %%%",
+        "info depth",
+        "info multipv pv",
+        "bestmove c2c4",
+    ]);
+    assert_eq!(best_move(STARTING_FEN).unwrap(), "c2c4");
+}
+
+/// Italian game after 3... Nf6: the bot (White) can take on f7, check, castle or play quietly
+const ITALIAN: &str = "r1bqkb1r/pppp1ppp/2n2n2/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4";
+
+fn italian_script() {
+    script(&[
+        "info depth 12 multipv 1 score cp 30 pv e1g1 f6e4",
+        "info depth 12 multipv 2 score cp 25 pv d2d3 f8c5",
+        "info depth 12 multipv 3 score cp 10 pv c4f7 e8f7",
+        "info depth 12 multipv 4 score cp -150 pv f3g5 d7d5",
+        "bestmove e1g1",
+    ]);
+}
+
+#[test]
+fn same_seed_gives_the_same_weakened_move() {
+    let _guard = setup();
+    script(&[
+        "info depth 12 multipv 1 score cp 40 pv b1c3",
+        "info depth 12 multipv 2 score cp 30 pv d2d4",
+        "info depth 12 multipv 3 score cp -300 pv c4f7 e8f7",
+        "bestmove b1c3",
+    ]);
+    let weak = DifficultyProfile {
+        multipv: 3,
+        random_move_chance: 0.3,
+        blunder_chance: 0.3,
+        ..profile()
+    };
+    let play = |seed: u64| runtime().block_on(StockfishService::get_best_move(ITALIAN, &weak, Personality::Balanced, seed)).unwrap();
+    let mut moves = std::collections::HashSet::new();
+    for seed in 0..20 {
+        let played = play(seed);
+        assert_eq!(play(seed), played, "seed {}", seed);
+        moves.insert(played);
+    }
+    // The seeds do change the choices
+    assert!(moves.len() > 1, "{:?}", moves);
+}
+
+#[test]
+fn balanced_bot_plays_the_engine_move() {
+    let _guard = setup();
+    italian_script();
+    assert_eq!(best_move_as(ITALIAN, Personality::Balanced).unwrap(), "e1g1");
+}
+
+#[test]
+fn aggressive_bot_prefers_captures_and_checks() {
+    let _guard = setup();
+    italian_script();
+    assert_eq!(best_move_as(ITALIAN, Personality::Aggressive).unwrap(), "c4f7");
+}
+
+#[test]
+fn positional_and_defensive_bots_castle() {
+    let _guard = setup();
+    italian_script();
+    assert_eq!(best_move_as(ITALIAN, Personality::Positional).unwrap(), "e1g1");
+    assert_eq!(best_move_as(ITALIAN, Personality::Defensive).unwrap(), "e1g1");
+}
+
+#[test]
+fn personalities_keep_away_from_bad_candidates() {
+    let _guard = setup();
+    script(&[
+        "info depth 12 multipv 1 score cp 40 pv b1c3",
+        "info depth 12 multipv 2 score cp -200 pv c4f7 e8f7",
+        "bestmove b1c3",
+    ]);
+    assert_eq!(best_move_as(ITALIAN, Personality::Aggressive).unwrap(), "b1c3");
+}
+
+async fn test_database() -> SqlitePool {
+    // A single connection: every connection to sqlite::memory: opens its own database
+    let pool = SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect("sqlite::memory:")
+        .await
+        .expect("in-memory database");
+    sqlx::migrate!("./migrations").run(&pool).await.expect("migrations");
+    DifficultyProfiles::reload(&pool).await.expect("difficulty profiles");
+    pool
+}
+
+async fn new_user(pool: &SqlitePool) -> String {
+    let user = UserService::create_user("fake-engine".to_string());
+    create_user(pool, &user).await.expect("user");
+    user.id
+}
+
+/// Standard game at full strength, the player with White
+fn game_input(user_id: String) -> NewGameInput {
+    NewGameInput {
+        user_id,
+        difficulty: 20,
+        color: None,
+        start_fen: None,
+        variant: None,
+        chess960_position: None,
+        allow_takebacks: true,
+        time_control: None,
+        bot: None,
+        difficulty_profile: None,
+        seed: Some(1),
+        personality: None,
+    }
+}
+
+async fn start_game(pool: &SqlitePool) -> String {
+    let user_id = new_user(pool).await;
+    GameService::create_game(pool, game_input(user_id)).await.expect("game").id
+}
+
+#[test]
+fn game_plays_the_engine_reply() {
+    let _guard = setup();
+    runtime().block_on(async {
+        let pool = test_database().await;
+        let game_id = start_game(&pool).await;
+        script(&[
+            "info depth 10 multipv 1 score cp -20 pv e7e5 g1f3",
+            "bestmove e7e5 ponder g1f3",
+        ]);
+        let input = MakeMoveInput { game_id: game_id.clone(), player_move: "e2e4".to_string() };
+        let result = GameService::make_move(&pool, input).await.unwrap();
+        assert_eq!(result.stockfish_move, "e7e5");
+        assert_eq!(result.game.moves_count, 2);
+
+        let game = get_game_by_id(&pool, &game_id).await.unwrap().unwrap();
+        assert_eq!(game.fen, "rnbqkbnr/pppp1ppp/8/4p3/4P3/8/PPPP1PPP/RNBQKBNR w KQkq e6 0 2");
+    });
+}
+
+#[test]
+fn illegal_engine_move_is_an_error() {
+    let _guard = setup();
+    runtime().block_on(async {
+        let pool = test_database().await;
+        let game_id = start_game(&pool).await;
+        script(&["bestmove e7e4"]);
+        let input = MakeMoveInput { game_id: game_id.clone(), player_move: "e2e4".to_string() };
+        let error = GameService::make_move(&pool, input).await.unwrap_err();
+        assert!(error.contains("move error"), "{}", error);
+
+        // Nothing was saved
+        let game = get_game_by_id(&pool, &game_id).await.unwrap().unwrap();
+        assert_eq!(game.moves_count, 0);
+        assert_eq!(game.fen, STARTING_FEN);
+    });
+}
+
+#[test]
+fn engine_first_move_can_end_the_game() {
+    let _guard = setup();
+    runtime().block_on(async {
+        let pool = test_database().await;
+        let user_id = new_user(&pool).await;
+
+        // Back-rank mate in one for the engine
+        script(&["bestmove a1a8"]);
+        let input = NewGameInput {
+            color: Some("black".to_string()),
+            start_fen: Some("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1".to_string()),
+            ..game_input(user_id.clone())
+        };
+        let game = GameService::create_game(&pool, input).await.unwrap();
+        assert_eq!(game.status, "finished");
+        assert_eq!(game.result.as_deref(), Some("white"));
+        assert_eq!(game.termination, Some(Termination::Checkmate));
+
+        // The engine's quiet move reaches the fifty-move rule
+        script(&["bestmove a2a3"]);
+        let input = NewGameInput {
+            color: Some("black".to_string()),
+            start_fen: Some("8/8/8/4k3/8/8/R7/4K3 w - - 99 80".to_string()),
+            ..game_input(user_id)
+        };
+        let game = GameService::create_game(&pool, input).await.unwrap();
+        let stored = get_game_by_id(&pool, &game.id).await.unwrap().unwrap();
+        assert_eq!(stored.status, "finished");
+        assert_eq!(stored.result.as_deref(), Some("draw"));
+        assert_eq!(stored.draw_reason.as_deref(), Some("fifty_move_rule"));
+    });
+}
+
+#[test]
+fn imported_game_on_the_engine_turn_gets_its_reply() {
+    let _guard = setup();
+    runtime().block_on(async {
+        let pool = test_database().await;
+        let user_id = new_user(&pool).await;
+
+        script(&["bestmove e7e5"]);
+        let input = ImportPgnInput {
+            user_id,
+            pgn: "[Event \"Unfinished\"]\n\n1. e4 *\n".to_string(),
+            difficulty: 20,
+            color: None,
+            count_in_stats: false,
+        };
+        let games = GameService::import_pgn(&pool, input).await.unwrap();
+        assert_eq!(games[0].status, "active");
+        assert_eq!(games[0].moves_count, 2);
+        let moves = get_game_moves(&pool, &games[0].id).await.unwrap();
+        assert_eq!(moves.iter().map(|m| m.uci.as_str()).collect::<Vec<_>>(), ["e2e4", "e7e5"]);
+
+        // The player can carry on
+        script(&["bestmove b8c6"]);
+        let input = MakeMoveInput { game_id: games[0].id.clone(), player_move: "Nf3".to_string() };
+        let result = GameService::make_move(&pool, input).await.unwrap();
+        assert_eq!(result.stockfish_move, "b8c6");
+    });
+}
+
+#[test]
+fn simultaneous_moves_are_saved_once() {
+    let _guard = setup();
+    runtime().block_on(async {
+        let pool = test_database().await;
+        let game_id = start_game(&pool).await;
+
+        // Both requests read the game before either engine reply comes back
+        script(&["sleep 200", "bestmove e7e5"]);
+        let first = MakeMoveInput { game_id: game_id.clone(), player_move: "e2e4".to_string() };
+        let second = MakeMoveInput { game_id: game_id.clone(), player_move: "d2d4".to_string() };
+        let (first, second) = tokio::join!(GameService::make_move(&pool, first), GameService::make_move(&pool, second));
+        let errors: Vec<String> = [first, second].into_iter().filter_map(Result::err).collect();
+        assert_eq!(errors, ["Not your turn"]);
+
+        let game = get_game_by_id(&pool, &game_id).await.unwrap().unwrap();
+        assert_eq!(game.moves_count, 2);
+        assert_eq!(get_game_moves(&pool, &game_id).await.unwrap().len(), 2);
+    });
+}
+
+#[test]
+fn deleted_profile_falls_back_to_the_game_level() {
+    let _guard = setup();
+    runtime().block_on(async {
+        let pool = test_database().await;
+        let user_id = new_user(&pool).await;
+        let profile = DifficultyProfileInput {
+            name: "club".to_string(),
+            level: 3,
+            skill_level: 5,
+            movetime_ms: 50,
+            uci_elo: None,
+            multipv: 1,
+            random_move_chance: 0.0,
+            blunder_chance: 0.0,
+            any_candidate_chance: 0.0,
+            weak_candidate_chance: 0.0,
+            near_best_chance: 0.0,
+        };
+        DifficultyService::save_profile(&pool, profile).await.unwrap();
+        let input = NewGameInput {
+            difficulty_profile: Some("club".to_string()),
+            ..game_input(user_id)
+        };
+        let game = GameService::create_game(&pool, input).await.unwrap();
+        assert_eq!(DifficultyProfiles::global().for_game(&game).name, "club");
+
+        DifficultyService::delete_profile(&pool, "club").await.unwrap();
+        let fallback = DifficultyProfiles::global().for_game(&game);
+        // Level 3's own profile, not full strength
+        assert_eq!(fallback.name, "3");
+        assert!(fallback.skill_level < 20);
+    });
+}
+
+#[test]
+fn draw_offer_is_answered_without_stockfish() {
+    let _guard = setup();
+    runtime().block_on(async {
+        let pool = test_database().await;
+        let user_id = new_user(&pool).await;
+        // The player is a queen up
+        let input = NewGameInput {
+            start_fen: Some("4k3/8/8/8/8/8/8/3QK3 w - - 0 1".to_string()),
+            ..game_input(user_id)
+        };
+        let game = GameService::create_game(&pool, input).await.unwrap();
+
+        // The built-in engine judges the position instead, and the engine takes the draw
+        script(&["crash"]);
+        let result = GameService::offer_draw(&pool, &game.id).await.unwrap();
+        assert!(result.engine_evaluation < -500, "{}", result.engine_evaluation);
+        assert!(result.accepted);
+        assert_eq!(result.game.result.as_deref(), Some("draw"));
+    });
+}
+
+#[test]
+fn hint_comes_from_the_builtin_engine_without_stockfish() {
+    let _guard = setup();
+    runtime().block_on(async {
+        let pool = test_database().await;
+        let user_id = new_user(&pool).await;
+        // Back-rank mate in one for the player
+        let input = NewGameInput {
+            start_fen: Some("6k1/5ppp/8/8/8/8/8/R5K1 w - - 0 1".to_string()),
+            ..game_input(user_id)
+        };
+        let game = GameService::create_game(&pool, input).await.unwrap();
+
+        script(&["crash"]);
+        let hint = GameService::get_hint(&pool, &game.id).await.unwrap();
+        assert_eq!(hint.best_move.uci, "a1a8");
+        assert_eq!(hint.mate_in, Some(1));
+        assert_eq!(hint.pv, ["Ra8#"]);
+        assert_eq!(hint.hints_used, 1);
+    });
+}
+
+#[test]
+fn low_levels_play_with_stockfish_by_default() {
+    let _guard = setup();
+    runtime().block_on(async {
+        let pool = test_database().await;
+        let user_id = new_user(&pool).await;
+        let input = NewGameInput { difficulty: 2, ..game_input(user_id) };
+        let game = GameService::create_game(&pool, input).await.unwrap();
+        // The built-in engine only steps in when Stockfish is unavailable
+        assert_eq!(game.engine, DEFAULT_ENGINE);
+
+        script(&["info depth 10 multipv 1 score cp -20 pv e7e5", "bestmove e7e5"]);
+        let input = MakeMoveInput { game_id: game.id.clone(), player_move: "e2e4".to_string() };
+        let result = GameService::make_move(&pool, input).await.unwrap();
+        assert_eq!(result.game.moves_count, 2);
+    });
+}
+
+#[test]
+fn draw_offer_loses_to_a_move_saved_meanwhile() {
+    let _guard = setup();
+    runtime().block_on(async {
+        let pool = test_database().await;
+        let user_id = new_user(&pool).await;
+        let input = NewGameInput {
+            start_fen: Some("4k3/8/8/8/8/8/8/3QK3 w - - 0 1".to_string()),
+            ..game_input(user_id)
+        };
+        let game = GameService::create_game(&pool, input).await.unwrap();
+
+        // Without Stockfish the offer is judged by the built-in engine, which takes its time
+        script(&["crash"]);
+        let move_meanwhile = async {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            let input = MakeMoveInput { game_id: game.id.clone(), player_move: "e1e2".to_string() };
+            GameService::make_move(&pool, input).await
+        };
+        let (offer, played) = tokio::join!(GameService::offer_draw(&pool, &game.id), move_meanwhile);
+        assert_eq!(played.unwrap().game.moves_count, 2);
+        assert_eq!(offer.unwrap_err(), "The game changed meanwhile, try again");
+
+        let stored = get_game_by_id(&pool, &game.id).await.unwrap().unwrap();
+        assert_eq!(stored.status, "active");
+        assert_eq!(stored.moves_count, 2);
+    });
+}
+
+#[test]
+fn hint_does_not_undo_a_move_saved_meanwhile() {
+    let _guard = setup();
+    runtime().block_on(async {
+        let pool = test_database().await;
+        let user_id = new_user(&pool).await;
+        let input = NewGameInput {
+            start_fen: Some("4k3/8/8/8/8/8/8/3QK3 w - - 0 1".to_string()),
+            ..game_input(user_id)
+        };
+        let game = GameService::create_game(&pool, input).await.unwrap();
+
+        // Without Stockfish the hint comes from the built-in engine, which takes its time
+        script(&["crash"]);
+        let move_meanwhile = async {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            let input = MakeMoveInput { game_id: game.id.clone(), player_move: "e1e2".to_string() };
+            GameService::make_move(&pool, input).await
+        };
+        let (hint, played) = tokio::join!(GameService::get_hint(&pool, &game.id), move_meanwhile);
+        let played = played.unwrap();
+        assert_eq!(hint.unwrap_err(), "The game changed meanwhile, try again");
+
+        let stored = get_game_by_id(&pool, &game.id).await.unwrap().unwrap();
+        assert_eq!(stored.moves_count, 2);
+        assert_eq!(stored.fen, played.game.fen);
+        assert_eq!(stored.hints_used, 0);
+    });
+}
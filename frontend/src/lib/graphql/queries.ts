@@ -22,6 +22,8 @@ export const CREATE_GAME = `
             status
             movesCount
             startTime
+            engine
+            personality
         }
     }
 `;
@@ -47,8 +49,29 @@ export const MAKE_MOVE = `
                 piece
                 color
             }
+            playerMove {
+                ...MoveDetailsFields
+            }
+            engineMove {
+                ...MoveDetailsFields
+            }
         }
     }
+
+    fragment MoveDetailsFields on MoveDetails {
+        from
+        to
+        uci
+        san
+        piece
+        color
+        captured
+        isCheck
+        isCheckmate
+        castling
+        isEnPassant
+        promotion
+    }
 `;
 
 export const GET_USER_PROFILE = `
@@ -81,6 +104,194 @@ export const GET_USER_PROFILE = `
     }
 `;
 
+const LEGAL_MOVES_FIELDS = `
+    fragment LegalMovesFields on LegalMoves {
+        fen
+        sideToMove
+        isCheck
+        kingSquare
+        squares {
+            from
+            piece
+            targets
+            moves {
+                from
+                to
+                uci
+                san
+                piece
+                color
+                captured
+                isCheck
+                isCheckmate
+                castling
+                isEnPassant
+                promotion
+            }
+        }
+    }
+`;
+
+export const GET_LEGAL_MOVES = `
+    query GetLegalMoves($gameId: String!) {
+        legalMoves(gameId: $gameId) {
+            ...LegalMovesFields
+        }
+    }
+    ${LEGAL_MOVES_FIELDS}
+`;
+
+export const GET_LEGAL_MOVES_FOR_FEN = `
+    query GetLegalMovesForFen($fen: String!) {
+        legalMovesForFen(fen: $fen) {
+            ...LegalMovesFields
+        }
+    }
+    ${LEGAL_MOVES_FIELDS}
+`;
+
+export const EVALUATE = `
+    query Evaluate($fen: String!, $depth: Int) {
+        evaluate(fen: $fen, depth: $depth) {
+            fen
+            evaluationCp
+            mateIn
+            bestMove {
+                from
+                to
+                uci
+                san
+            }
+            pv
+            depth
+        }
+    }
+`;
+
+export const ANALYSE_POSITION = `
+    query AnalysePosition($input: AnalysisInput!) {
+        analysePosition(input: $input) {
+            fen
+            lines {
+                rank
+                evaluationCp
+                mateIn
+                depth
+                pv
+                pvUci
+            }
+        }
+    }
+`;
+
+export const GET_GAME_ANALYSIS = `
+    query GetGameAnalysis($gameId: String!) {
+        game(gameId: $gameId) {
+            id
+            analysis {
+                status
+                whiteAccuracy
+                blackAccuracy
+                moves {
+                    ply
+                    side
+                    uci
+                    san
+                    evalBeforeCp
+                    evalAfterCp
+                    mateIn
+                    bestMove
+                    bestMoveSan
+                    cpLoss
+                    classification
+                }
+            }
+        }
+    }
+`;
+
+export const ANALYSE_GAME = `
+    mutation AnalyseGame($gameId: String!) {
+        analyseGame(gameId: $gameId) {
+            id
+            status
+        }
+    }
+`;
+
+export const TAKE_BACK = `
+    mutation TakeBack($gameId: String!) {
+        takeBack(gameId: $gameId) {
+            id
+            fen
+            movesCount
+            takebacks
+        }
+    }
+`;
+
+export const GET_HINT = `
+    mutation GetHint($gameId: String!) {
+        getHint(gameId: $gameId) {
+            bestMove {
+                from
+                to
+                uci
+                san
+                piece
+                color
+            }
+            evaluationCp
+            mateIn
+            pv
+            depth
+            hintsUsed
+        }
+    }
+`;
+
+export const RESIGN_GAME = `
+    mutation ResignGame($gameId: String!) {
+        resignGame(gameId: $gameId) {
+            id
+            status
+            result
+            termination
+            endTime
+            durationSeconds
+        }
+    }
+`;
+
+export const ABORT_GAME = `
+    mutation AbortGame($gameId: String!) {
+        abortGame(gameId: $gameId) {
+            id
+            status
+            termination
+            endTime
+        }
+    }
+`;
+
+export const OFFER_DRAW = `
+    mutation OfferDraw($gameId: String!) {
+        offerDraw(gameId: $gameId) {
+            accepted
+            engineEvaluation
+            game {
+                id
+                status
+                result
+                termination
+                drawReason
+                endTime
+                durationSeconds
+            }
+        }
+    }
+`;
+
 export const GET_LEADERBOARD = `
     query GetLeaderboard($limit: Int) {
         getLeaderboard(limit: $limit) {
@@ -92,3 +303,28 @@ export const GET_LEADERBOARD = `
         }
     }
 `;
+
+export const GET_BOTS = `
+    query GetBots {
+        bots
+    }
+`;
+
+export const GET_DIFFICULTY_PROFILES = `
+    query GetDifficultyProfiles {
+        difficultyProfiles {
+            name
+            level
+            skillLevel
+            movetimeMs
+            uciElo
+            multipv
+            randomMoveChance
+            blunderChance
+            anyCandidateChance
+            weakCandidateChance
+            nearBestChance
+            updatedAt
+        }
+    }
+`;
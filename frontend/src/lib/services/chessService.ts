@@ -3,14 +3,36 @@ import {
 	CREATE_USER,
 	CREATE_GAME,
 	MAKE_MOVE,
+	TAKE_BACK,
+	GET_LEGAL_MOVES,
+	GET_LEGAL_MOVES_FOR_FEN,
+	EVALUATE,
+	ANALYSE_POSITION,
+	GET_GAME_ANALYSIS,
+	ANALYSE_GAME,
+	GET_HINT,
+	RESIGN_GAME,
+	ABORT_GAME,
+	OFFER_DRAW,
 	GET_USER_PROFILE,
-	GET_LEADERBOARD
+	GET_LEADERBOARD,
+	GET_BOTS,
+	GET_DIFFICULTY_PROFILES
 } from '$lib/graphql/queries';
 import type {
 	User,
 	Game,
 	UserProfile,
 	GameMoveResult,
+	DrawOfferResult,
+	Hint,
+	LegalMoves,
+	Evaluation,
+	AnalysisInput,
+	PositionAnalysis,
+	GameAnalysis,
+	DifficultyProfile,
+	Personality,
 	ChessSquare,
 	ChessPiece
 } from '$lib/types/chess';
@@ -34,16 +56,43 @@ export class ChessService {
 	 * Starts a new chess game against Stockfish
 	 * @param userId - User ID who will play the game
 	 * @param difficulty - Stockfish difficulty level (1-20)
+	 * @param bot - Optional bot to play against (see getBots)
+	 * @param difficultyProfile - Optional named profile, played at its own level
+	 * @param personality - Optional style of the bot's moves (balanced by default)
 	 * @returns Promise with new game data including initial position
-	 * @throws Error if user doesn't exist or invalid difficulty
+	 * @throws Error if user doesn't exist, invalid difficulty, unknown bot or profile
 	 */
-	static async createGame(userId: string, difficulty: number): Promise<Game> {
+	static async createGame(
+		userId: string,
+		difficulty: number,
+		bot?: string,
+		difficultyProfile?: string,
+		personality?: Personality
+	): Promise<Game> {
 		const result = await executeGraphQL(CREATE_GAME, {
-			input: { userId, difficulty }
+			input: { userId, difficulty, bot, difficultyProfile, personality }
 		});
 		return result.createGame;
 	}
 
+	/**
+	 * Lists the bots configured on the server
+	 * @returns Promise with the bot names, sorted
+	 */
+	static async getBots(): Promise<string[]> {
+		const result = await executeGraphQL(GET_BOTS);
+		return result.bots;
+	}
+
+	/**
+	 * Lists the difficulty profiles ("1" to "20" back the difficulty levels)
+	 * @returns Promise with the profiles, by level then name
+	 */
+	static async getDifficultyProfiles(): Promise<DifficultyProfile[]> {
+		const result = await executeGraphQL(GET_DIFFICULTY_PROFILES);
+		return result.difficultyProfiles;
+	}
+
 	/**
 	 * Makes a move in the current game
 	 * @param gameId - ID of the active game
@@ -58,6 +107,119 @@ export class ChessService {
 		return result.makeMove;
 	}
 
+	/**
+	 * Lists the legal moves of a game's current position, grouped by origin square
+	 * @param gameId - ID of the game
+	 * @returns Promise with the moves, check status and king square
+	 */
+	static async getLegalMoves(gameId: string): Promise<LegalMoves> {
+		const result = await executeGraphQL(GET_LEGAL_MOVES, { gameId });
+		return result.legalMoves;
+	}
+
+	/**
+	 * Lists the legal moves of any position, grouped by origin square
+	 * @param fen - Position in FEN notation
+	 * @returns Promise with the moves, check status and king square
+	 * @throws Error if the FEN is invalid
+	 */
+	static async getLegalMovesForFen(fen: string): Promise<LegalMoves> {
+		const result = await executeGraphQL(GET_LEGAL_MOVES_FOR_FEN, { fen });
+		return result.legalMovesForFen;
+	}
+
+	/**
+	 * Evaluates a position with Stockfish at full strength (e.g., for an evaluation bar)
+	 * @param fen - Position in FEN notation
+	 * @param depth - Search depth (server default when omitted)
+	 * @returns Promise with the score from White's point of view, best move and PV
+	 */
+	static async evaluate(fen: string, depth?: number): Promise<Evaluation> {
+		const result = await executeGraphQL(EVALUATE, { fen, depth });
+		return result.evaluate;
+	}
+
+	/**
+	 * Returns the top Stockfish lines of a FEN or of a game position (analysis board)
+	 * @param input - FEN or gameId + ply, number of lines and depth/time limits
+	 * @returns Promise with the lines from best to worst, scored from White's point of view
+	 */
+	static async analysePosition(input: AnalysisInput): Promise<PositionAnalysis> {
+		const result = await executeGraphQL(ANALYSE_POSITION, { input });
+		return result.analysePosition;
+	}
+
+	/**
+	 * Gets the post-game analysis of a game (per-move verdicts and accuracy once done)
+	 * @param gameId - ID of the game
+	 * @returns Promise with the analysis, null when none was requested
+	 */
+	static async getGameAnalysis(gameId: string): Promise<GameAnalysis | null> {
+		const result = await executeGraphQL(GET_GAME_ANALYSIS, { gameId });
+		return result.game?.analysis ?? null;
+	}
+
+	/**
+	 * Queues a finished game for analysis (finished games are queued automatically)
+	 * @param gameId - ID of the finished game
+	 * @throws Error if the game is not finished
+	 */
+	static async analyseGame(gameId: string): Promise<void> {
+		await executeGraphQL(ANALYSE_GAME, { gameId });
+	}
+
+	/**
+	 * Takes back the player's last move and Stockfish's reply
+	 * @param gameId - ID of the active game
+	 * @returns Promise with the game restored to the previous position
+	 * @throws Error if takebacks are disabled or there is no move to take back
+	 */
+	static async takeBack(gameId: string): Promise<Game> {
+		const result = await executeGraphQL(TAKE_BACK, { gameId });
+		return result.takeBack;
+	}
+
+	/**
+	 * Asks Stockfish for the best move at full strength (games with hints set no records)
+	 * @param gameId - ID of the active game
+	 * @returns Promise with the best move, evaluation and principal variation
+	 */
+	static async getHint(gameId: string): Promise<Hint> {
+		const result = await executeGraphQL(GET_HINT, { gameId });
+		return result.getHint;
+	}
+
+	/**
+	 * Resigns the game, Stockfish wins
+	 * @param gameId - ID of the active game
+	 * @returns Promise with the finished game
+	 */
+	static async resignGame(gameId: string): Promise<Game> {
+		const result = await executeGraphQL(RESIGN_GAME, { gameId });
+		return result.resignGame;
+	}
+
+	/**
+	 * Aborts a game during its first plies (not counted in statistics)
+	 * @param gameId - ID of the active game
+	 * @returns Promise with the aborted game
+	 * @throws Error once the game is too far along to be aborted
+	 */
+	static async abortGame(gameId: string): Promise<Game> {
+		const result = await executeGraphQL(ABORT_GAME, { gameId });
+		return result.abortGame;
+	}
+
+	/**
+	 * Offers a draw to Stockfish
+	 * @param gameId - ID of the active game
+	 * @returns Promise with whether the offer was accepted and the game
+	 */
+	static async offerDraw(gameId: string): Promise<DrawOfferResult> {
+		const result = await executeGraphQL(OFFER_DRAW, { gameId });
+		return result.offerDraw;
+	}
+
 	/**
 	 * Retrieves complete user profile with statistics and records
 	 * @param userId - User ID to fetch profile for
@@ -22,6 +22,71 @@ export interface Game {
 	endTime?: string;
 	durationSeconds?: number;
 	movesCount: number;
+	playerColor: 'white' | 'black';
+	variant: 'standard' | 'chess960';
+	termination?: Termination;
+	drawReason?: string;
+	takebacks: number;
+	takebacksAllowed: boolean;
+	hintsUsed: number;
+	engine: string;
+	difficultyProfile?: string;
+	rngSeed: number;
+	personality: Personality;
+	timeControl?: string;
+	whiteTimeLeftMs?: number;
+	blackTimeLeftMs?: number;
+	analysis?: GameAnalysis;
+}
+
+export interface TimeControlInput {
+	baseSeconds?: number;
+	incrementSeconds?: number;
+	daysPerMove?: number;
+}
+
+export type Termination =
+	| 'CHECKMATE'
+	| 'STALEMATE'
+	| 'INSUFFICIENT_MATERIAL'
+	| 'FIFTY_MOVE_RULE'
+	| 'THREEFOLD_REPETITION'
+	| 'RESIGNATION'
+	| 'AGREEMENT'
+	| 'ABORTED'
+	| 'TIMEOUT';
+
+/** Style of the bot's moves */
+export type Personality = 'BALANCED' | 'AGGRESSIVE' | 'POSITIONAL' | 'DEFENSIVE' | 'TRAPPY';
+
+export interface DrawOfferResult {
+	accepted: boolean;
+	engineEvaluation: number;
+	game: Game;
+}
+
+export interface DifficultyProfile {
+	name: string;
+	level: number;
+	skillLevel: number;
+	movetimeMs: number;
+	uciElo?: number;
+	multipv: number;
+	randomMoveChance: number;
+	blunderChance: number;
+	anyCandidateChance: number;
+	weakCandidateChance: number;
+	nearBestChance: number;
+	updatedAt: string;
+}
+
+export interface Hint {
+	bestMove: MoveDetails;
+	evaluationCp?: number;
+	mateIn?: number;
+	pv: string[];
+	depth: number;
+	hintsUsed: number;
 }
 
 export interface UserRecord {
@@ -59,6 +124,95 @@ export interface GameMoveResult {
 	moveTimeMs?: number;
 	totalTimeSeconds?: number;
 	lastMove?: LastMove;
+	playerMove: MoveDetails;
+	engineMove?: MoveDetails;
+}
+
+export interface MoveDetails {
+	from: string;
+	to: string;
+	uci: string;
+	san: string;
+	piece: string;
+	color: string;
+	captured?: string;
+	isCheck: boolean;
+	isCheckmate: boolean;
+	castling?: 'kingside' | 'queenside';
+	isEnPassant: boolean;
+	promotion?: string;
+}
+
+export interface LegalMoves {
+	fen: string;
+	sideToMove: 'white' | 'black';
+	isCheck: boolean;
+	kingSquare: string;
+	squares: SquareMoves[];
+}
+
+export interface SquareMoves {
+	from: string;
+	piece: string;
+	targets: string[];
+	moves: MoveDetails[];
+}
+
+export interface Evaluation {
+	fen: string;
+	evaluationCp?: number;
+	mateIn?: number;
+	bestMove?: MoveDetails;
+	pv: string[];
+	depth: number;
+}
+
+export interface AnalysisInput {
+	fen?: string;
+	gameId?: string;
+	ply?: number;
+	lines?: number;
+	depth?: number;
+	movetimeMs?: number;
+}
+
+export interface PositionAnalysis {
+	fen: string;
+	lines: AnalysisLine[];
+}
+
+export interface AnalysisLine {
+	rank: number;
+	evaluationCp?: number;
+	mateIn?: number;
+	depth: number;
+	pv: string[];
+	pvUci: string[];
+}
+
+export type AnalysisStatus = 'PENDING' | 'RUNNING' | 'DONE' | 'FAILED';
+
+export type MoveClassification = 'BEST' | 'GOOD' | 'INACCURACY' | 'MISTAKE' | 'BLUNDER';
+
+export interface GameAnalysis {
+	status: AnalysisStatus;
+	whiteAccuracy?: number;
+	blackAccuracy?: number;
+	moves: MoveAnalysis[];
+}
+
+export interface MoveAnalysis {
+	ply: number;
+	side: 'white' | 'black';
+	uci: string;
+	san: string;
+	evalBeforeCp: number;
+	evalAfterCp: number;
+	mateIn?: number;
+	bestMove?: string;
+	bestMoveSan?: string;
+	cpLoss: number;
+	classification: MoveClassification;
 }
 
 export interface LastMove {